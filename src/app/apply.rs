@@ -22,14 +22,23 @@
  * SOFTWARE.
  */
 use std::fs;
-use crate::app::change_file;
-use crate::config::Config;
+use crate::app::{change_file, check_sources, is_active, missing_variables, placeholder, resolve_location};
+use crate::app::transform;
+use crate::app::{ApplyError, MissingVariable, Selection, Summary};
+use crate::config::{Config, OnMissing};
+use crate::env::Resolver;
 use crate::app::parse_variable;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use filetime::{set_file_times, FileTime};
 
-pub async fn apply(config: &Config) -> Result<u32> {
-    let mut changes = 0;
+pub async fn apply(config: &Config, env: &Resolver, selection: &Selection) -> Result<Summary> {
+    let mut summary = Summary::default();
+
+    selection.check_ids(config)?;
+
+    // Resolve every location first so nothing is written if a variable is missing
+    let mut missing = missing_variables(config, env, selection);
+    let mut planned = Vec::new();
 
     for (file_format, file_config) in config.specific.iter() {
         for loc in &file_config.locations {
@@ -37,55 +46,97 @@ pub async fn apply(config: &Config) -> Result<u32> {
                 continue;
             }
 
+            if !selection.matches(file_format, loc) {
+                continue;
+            }
+
             if loc.file.is_empty() {
                 continue;
             }
 
-            let replacement = if let Ok(environment_variable) = std::env::var(&loc.variable) {
-                environment_variable
-            } else {
-                println!("Environment variable {} not found", &loc.variable);
+            if !is_active(config, env, loc) {
                 continue;
-            };
+            }
 
-            for file in &loc.file {
-                if !file.exists() {
-                    continue;
-                }
+            let label = loc.label();
+            let (replacement, value_type) = match resolve_location(config, env, loc) {
+                Ok(value) => (
+                    transform::apply_all(&loc.transform, value)
+                        .with_context(|| format!("Failed to transform {} ({})", label, file_format))?,
+                    loc.value_type,
+                ),
+                Err(names) => {
+                    check_sources(env, &names)?;
+                    let policy = loc.on_missing.unwrap_or(config.environment.on_missing);
+                    summary.missing.push(MissingVariable {
+                        file_format,
+                        variables: names.clone(),
+                        files: loc.file.clone(),
+                        policy,
+                    });
 
-                if loc.node.is_empty() {
-                    continue;
+                    match policy {
+                        OnMissing::Skip => continue,
+                        OnMissing::Error => {
+                            for name in names {
+                                if !missing.contains(&name) {
+                                    missing.push(name);
+                                }
+                            }
+                            continue;
+                        }
+                        OnMissing::Default => match &loc.fallback {
+                            Some(fallback) => (fallback.clone(), loc.value_type),
+                            None => return Err(ApplyError::MissingFallback(names.join(", ")).into()),
+                        },
+                        // Placeholders are never valid typed values, so they're written like deapply does
+                        OnMissing::Placeholder => (placeholder(config, file_config, loc), None),
+                    }
                 }
+            };
+
+            let formatted = parse_variable(file_format, &replacement, value_type)
+                .with_context(|| format!("Invalid value for {} ({})", label, file_format))?;
+            planned.push((file_format, loc, formatted));
+        }
+    }
 
-                let mut file_changed = false;
-                let original_metadata = fs::metadata(file)?;
-                let original_mtime = FileTime::from_last_modification_time(&original_metadata);
-                let original_atime = FileTime::from_last_access_time(&original_metadata);
+    if !missing.is_empty() {
+        check_sources(env, &missing)?;
+        return Err(ApplyError::MissingVariables(missing).into());
+    }
 
-                for node in &loc.node {
-                    if node.is_empty() || node.trim().is_empty() {
-                        continue;
-                    }
+    for (file_format, loc, formatted) in planned {
+        for file in &loc.file {
+            if !file.exists() || !selection.matches_file(file) {
+                continue;
+            }
 
-                    change_file(
-                        file_format,
-                        file,
-                        node,
-                        parse_variable(file_format, &replacement).as_str(),
-                    )
-                    .await?;
-                    file_changed = true;
-                    changes += 1;
-                }
+            if loc.node.is_empty() {
+                continue;
+            }
+
+            let mut file_changed = false;
+            let original_metadata = fs::metadata(file)?;
+            let original_mtime = FileTime::from_last_modification_time(&original_metadata);
+            let original_atime = FileTime::from_last_access_time(&original_metadata);
 
-                // Preserve original file metadata
-                if file_changed {
-                    set_file_times(file, original_atime, original_mtime)?;
+            for node in &loc.node {
+                if node.is_empty() || node.trim().is_empty() {
+                    continue;
                 }
+
+                change_file(file_format, file, node, &formatted).await?;
+                file_changed = true;
+                summary.changes += 1;
+            }
+
+            // Preserve original file metadata
+            if file_changed {
+                set_file_times(file, original_atime, original_mtime)?;
             }
         }
     }
 
-    Ok(changes)
+    Ok(summary)
 }
-
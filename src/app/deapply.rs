@@ -22,13 +22,15 @@
  * SOFTWARE.
  */
 use std::fs;
-use crate::app::change_file;
+use crate::app::{change_file, is_active, placeholder, Selection};
 use crate::config::Config;
+use crate::env::Resolver;
 use crate::app::parse_variable;
 use anyhow::Result;
 use filetime::{set_file_times, FileTime};
 
-pub async fn deapply(config: &Config) -> Result<u32> {
+pub async fn deapply(config: &Config, env: &Resolver, selection: &Selection) -> Result<u32> {
+    selection.check_ids(config)?;
     let mut changes = 0;
 
     for (file_format, file_config) in config.specific.iter() {
@@ -37,21 +39,22 @@ pub async fn deapply(config: &Config) -> Result<u32> {
                 continue;
             }
 
+            if !selection.matches(file_format, loc) {
+                continue;
+            }
+
             if loc.file.is_empty() {
                 continue;
             }
 
-            let replacement = if let Some(node) = &loc.default {
-                node
-            } else {
-                &format!(
-                    "{}{}{}",
-                    &config.environment.prefix, &loc.variable, &config.environment.suffix
-                )
-            };
+            if !is_active(config, env, loc) {
+                continue;
+            }
+
+            let replacement = parse_variable(file_format, &placeholder(config, file_config, loc), None)?;
 
             for file in &loc.file {
-                if !file.exists() {
+                if !file.exists() || !selection.matches_file(file) {
                     continue;
                 }
 
@@ -69,13 +72,7 @@ pub async fn deapply(config: &Config) -> Result<u32> {
                         continue;
                     }
 
-                    change_file(
-                        file_format,
-                        file,
-                        node,
-                        parse_variable(file_format, &replacement).as_str(),
-                    )
-                    .await?;
+                    change_file(file_format, file, node, &replacement).await?;
                     file_changed = true;
                     changes += 1;
                 }
@@ -21,29 +21,36 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::app::{is_active, resolve_location, Selection};
 use crate::config::Config;
+use crate::env::Resolver;
 use anyhow::Result;
 use std::collections::HashSet;
 
-pub async fn get(config: &Config) -> Result<HashSet<String>> {
+pub async fn get(config: &Config, env: &Resolver, selection: &Selection) -> Result<HashSet<String>> {
+    selection.check_ids(config)?;
     let mut unique_paths = HashSet::new();
 
-    for (_file_format, file_config) in config.specific.iter() {
+    for (file_format, file_config) in config.specific.iter() {
         for loc in &file_config.locations {
             if loc.override_settings.exempt_apply {
                 continue;
             }
 
+            if !selection.matches(file_format, loc) {
+                continue;
+            }
+
             if loc.file.is_empty() {
                 continue;
             }
 
-            if let Ok(_environment_variable) = std::env::var(&loc.variable) {} else {
+            if !is_active(config, env, loc) || resolve_location(config, env, loc).is_err() {
                 continue;
-            };
+            }
 
             for file in &loc.file {
-                if !file.exists() {
+                if !file.exists() || !selection.matches_file(file) {
                     continue;
                 }
 
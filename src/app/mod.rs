@@ -25,16 +25,282 @@
 mod apply;
 mod deapply;
 mod files;
+mod selection;
+pub mod transform;
+mod validate;
+pub mod value;
 
 pub use apply::apply;
 pub use deapply::deapply;
 pub use files::get;
-use std::path::PathBuf;
-use std::str::FromStr;
+pub use selection::Selection;
+pub use validate::{validate, Diagnostic, Severity};
+pub use value::parse_variable;
+use crate::config::{Config, FileTypeConfig, Location, OnMissing, When};
+use crate::env::{parser, read_secret, Resolution, Resolver};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ApplyError {
+    #[error("Missing required environment variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    #[error("No fallback configured for {0} which uses on_missing = \"default\"")]
+    MissingFallback(String),
+    #[error("Cannot resolve variables in file or node paths: {}", .0.join(", "))]
+    UnresolvedPaths(Vec<String>),
+    #[error("No location has the id: {}", .0.join(", "))]
+    UnknownIds(Vec<String>),
+    #[error("Cannot read {name}: {message}")]
+    Source { name: String, message: String },
+}
+
+/// Outcome of an `apply` run.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub changes: u32,
+    /// Locations whose variable was missing, and the policy that handled each of them
+    pub missing: Vec<MissingVariable>,
+}
+
+#[derive(Debug)]
+pub struct MissingVariable {
+    pub file_format: &'static str,
+    pub variables: Vec<String>,
+    pub files: Vec<PathBuf>,
+    pub policy: OnMissing,
+}
+
+impl fmt::Display for MissingVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: Vec<String> = self.files.iter().map(|p| p.display().to_string()).collect();
+        write!(
+            f,
+            "{} ({}: {}) -> {}",
+            self.variables.join(", "),
+            self.file_format,
+            files.join(", "),
+            self.policy.as_str()
+        )
+    }
+}
+
+/// The value `deapply` writes back for a location.
+///
+/// Uses the first of the location's `default`, its `placeholder`, its `value` template, the
+/// format's `placeholder` and the global prefix/suffix.
+fn placeholder(config: &Config, file_config: &FileTypeConfig, loc: &Location) -> String {
+    if let Some(default) = &loc.default {
+        default.clone()
+    } else if let Some(placeholder) = &loc.placeholder {
+        placeholder.render(&loc.placeholder_name())
+    } else if let Some(template) = &loc.value {
+        template.clone()
+    } else if let Some(placeholder) = &file_config.placeholder {
+        placeholder.render(&loc.placeholder_name())
+    } else {
+        format!(
+            "{}{}{}",
+            &config.environment.prefix, loc.placeholder_name(), &config.environment.suffix
+        )
+    }
+}
+
+/// Resolves a variable from the environment, falling back to its `[environment]` default.
+pub fn resolve_variable(config: &Config, env: &Resolver, name: &str) -> Option<Resolution> {
+    env.resolve(name).or_else(|| {
+        let variable = config.environment.variables.iter().find(|v| v.name == name)?;
+        Some(Resolution {
+            value: variable.default.clone()?,
+            source: "default in config".to_string(),
+        })
+    })
+}
+
+fn lookup_variable(config: &Config, env: &Resolver, name: &str) -> Option<String> {
+    resolve_variable(config, env, name).map(|resolution| resolution.value)
+}
+
+/// Resolves the value written for a location, or returns the names of the missing variables.
+///
+/// A `value` template only resolves when every variable it references does, a `variable_file`
+/// when it can be read.
+fn resolve_location(config: &Config, env: &Resolver, loc: &Location) -> Result<String, Vec<String>> {
+    match (&loc.value, &loc.variable_file) {
+        (Some(template), _) => {
+            let missing = parser::unresolved(template, |name| lookup_variable(config, env, name));
+            if !missing.is_empty() {
+                return Err(missing);
+            }
+            parser::expand(template, |name| lookup_variable(config, env, name)).map_err(|e| vec![e.name])
+        }
+        (None, Some(file)) => read_secret(file).map_err(|_| vec![loc.label().to_string()]),
+        (None, None) => lookup_variable(config, env, &loc.variable).ok_or_else(|| vec![loc.variable.clone()]),
+    }
+}
+
+/// Expands `${VAR}` references in the file and node paths of the active locations `selection`
+/// selects. Any other `$` is literal, as in a `$schema` node.
+///
+/// Fails listing every reference which cannot be resolved, rather than leaving a path which
+/// would silently match nothing. Paths using such a reference, and those of other locations,
+/// are left as written.
+pub fn interpolate_paths(
+    config: &mut Config,
+    env: &Resolver,
+    selection: &Selection,
+) -> Result<(), ApplyError> {
+    let lookup = |name: &str| lookup_variable(config, env, name);
+    let mut unresolved = Vec::new();
+    let mut expanded = Vec::new();
+
+    for (file_format, file_config) in config.specific.iter() {
+        for loc in &file_config.locations {
+            if !selection.matches(file_format, loc) || !is_active(config, env, loc) {
+                expanded.push(None);
+                continue;
+            }
+
+            let files: Vec<String> = loc
+                .file
+                .iter()
+                .map(|f| f.to_string_lossy().to_string())
+                .collect();
+            let paths = files.iter().chain(&loc.node);
+            let names: Vec<String> =
+                paths.flat_map(|p| parser::unresolved_braced(p, lookup)).collect();
+            if !names.is_empty() {
+                unresolved.extend(names.into_iter().map(|name| match &loc.origin {
+                    Some(origin) => format!("{} ({})", name, origin),
+                    None => name,
+                }));
+                expanded.push(None);
+                continue;
+            }
+
+            // Every reference resolves, so expanding cannot fail
+            let expand = |text: &str| parser::expand_braced(text, lookup).unwrap_or_default();
+            let files: Vec<PathBuf> = loc
+                .file
+                .iter()
+                .zip(files)
+                .map(|(file, text)| match text.contains("${") {
+                    true => PathBuf::from(expand(&text)),
+                    false => file.clone(),
+                })
+                .collect();
+            let nodes = loc.node.iter().map(|n| expand(n)).collect();
+            expanded.push(Some((files, nodes)));
+        }
+    }
+
+    let locations = config.specific.iter_mut().flat_map(|(_, fc)| fc.locations.iter_mut());
+    for (loc, paths) in locations.zip(expanded) {
+        if let Some((files, nodes)) = paths {
+            loc.file = files;
+            loc.node = nodes;
+        }
+    }
+
+    match unresolved.is_empty() {
+        true => Ok(()),
+        false => Err(ApplyError::UnresolvedPaths(unresolved)),
+    }
+}
+
+/// Whether a location's `when` condition holds, locations without one are always active.
+fn is_active(config: &Config, env: &Resolver, loc: &Location) -> bool {
+    loc.when.as_ref().is_none_or(|when| condition_holds(config, env, when))
+}
+
+fn condition_holds(config: &Config, env: &Resolver, when: &When) -> bool {
+    let env_holds = when.env.as_ref().is_none_or(|name| {
+        let value = lookup_variable(config, env, name);
+        match &when.equals {
+            Some(expected) => value.as_ref() == Some(expected),
+            None => value.is_some_and(|v| !v.is_empty()),
+        }
+    });
+
+    env_holds
+        && when.exists.as_ref().is_none_or(|name| lookup_variable(config, env, name).is_some())
+        && when.profile.as_ref().is_none_or(|profile| config.active_profile.as_ref() == Some(profile))
+        && when.all.as_ref().is_none_or(|all| all.iter().all(|w| condition_holds(config, env, w)))
+        && when.any.as_ref().is_none_or(|any| any.iter().any(|w| condition_holds(config, env, w)))
+        && when.not.as_ref().is_none_or(|not| !condition_holds(config, env, not))
+}
+
+/// Returns the names of every variable the config declares or writes, sorted.
+pub fn referenced_variables(config: &Config) -> Vec<String> {
+    let mut names: BTreeSet<String> =
+        config.environment.variables.iter().map(|v| v.name.clone()).collect();
+    for (_, file_config) in config.specific.iter() {
+        for loc in &file_config.locations {
+            names.extend(location_variables(loc));
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// The variables a location writes, directly or through its `value` template.
+fn location_variables(loc: &Location) -> Vec<String> {
+    match &loc.value {
+        Some(template) => parser::references(template),
+        None if !loc.variable.is_empty() => vec![loc.variable.clone()],
+        None => Vec::new(),
+    }
+}
+
+/// Returns the names of the required variables which cannot be resolved.
+///
+/// A selection with filters only checks the required variables written by the locations it
+/// selects, so applying a subset isn't blocked by variables it doesn't need.
+fn missing_variables(config: &Config, env: &Resolver, selection: &Selection) -> Vec<String> {
+    let used: Option<BTreeSet<String>> = (!selection.is_empty()).then(|| {
+        config
+            .specific
+            .iter()
+            .flat_map(|(file_format, file_config)| {
+                file_config
+                    .locations
+                    .iter()
+                    .filter(move |loc| selection.matches(file_format, loc))
+            })
+            .filter(|loc| loc.file.iter().any(|file| selection.matches_file(file)))
+            .filter(|loc| is_active(config, env, loc))
+            .flat_map(location_variables)
+            .collect()
+    });
+
+    config
+        .environment
+        .variables
+        .iter()
+        .filter(|v| used.as_ref().is_none_or(|used| used.contains(&v.name)))
+        .filter(|v| lookup_variable(config, env, &v.name).is_none())
+        .map(|v| v.name.clone())
+        .collect()
+}
+
+/// Fails when a source such as Vault couldn't read one of `names`, which would otherwise be
+/// handled as unset.
+fn check_sources(env: &Resolver, names: &[String]) -> Result<(), ApplyError> {
+    for name in names {
+        if let Some(message) = env.source_error(name) {
+            return Err(ApplyError::Source {
+                name: name.clone(),
+                message,
+            });
+        }
+    }
+    Ok(())
+}
 
 async fn change_file(
     file_type: &'static str,
-    file: &PathBuf,
+    file: &Path,
     node: &str,
     value: &str,
 ) -> anyhow::Result<()> {
@@ -49,25 +315,15 @@ async fn change_file(
     }
 }
 
-pub fn parse_variable(file_type: &'static str, env: &str) -> String {
+/// Returns the line of a node in the contents of a file, or `None` if the node doesn't exist.
+fn find_node(file_type: &str, content: &str, node: &str) -> anyhow::Result<Option<usize>> {
     match file_type {
-        "yaml" | "json" | "toml" | "hocon" => {
-            if let Ok(numeric_value) = f64::from_str(env) {
-                if !env.trim().is_empty() {
-                    return numeric_value.to_string();
-                }
-            }
-            format!("\"{}\"", env)
-        },
-         | "properties" => {
-             if let Ok(numeric_value) = f64::from_str(env) {
-                 if !env.trim().is_empty() {
-                     return numeric_value.to_string();
-                 }
-             }
-             format!("{}", env)
-         },
-        _ => String::new()
+        "yaml" => Ok(crate::utils::yaml::find_yaml_node(content, node)),
+        "json" => crate::utils::json::find_json_node(content, node),
+        "toml" => crate::utils::toml::find_toml_node(content, node),
+        "xml" => crate::utils::xml::find_xml_node(content, node),
+        "properties" => Ok(crate::utils::properties::find_properties_node(content, node)),
+        "hocon" => Ok(crate::utils::hocon::find_hocon_node(content, node)),
+        _ => Ok(None),
     }
-
-}
\ No newline at end of file
+}
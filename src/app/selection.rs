@@ -0,0 +1,94 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::app::{location_variables, ApplyError};
+use crate::config::{Config, Location};
+use std::path::{Path, PathBuf};
+
+/// Narrows down which locations and files a command touches.
+///
+/// Every non-empty filter must match, empty filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Locations having at least one of these tags
+    pub tags: Vec<String>,
+    /// Locations having none of these tags
+    pub exclude_tags: Vec<String>,
+    pub ids: Vec<String>,
+    /// Locations writing one of these variables, directly or through a `value` template
+    pub variables: Vec<String>,
+    pub formats: Vec<String>,
+    pub files: Vec<PathBuf>,
+}
+
+impl Selection {
+    /// Whether no filter is set, so every location is selected.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.exclude_tags.is_empty()
+            && self.ids.is_empty()
+            && self.variables.is_empty()
+            && self.formats.is_empty()
+            && self.files.is_empty()
+    }
+
+    /// Fails if an id filter names no location of the config, which is likely a typo.
+    pub fn check_ids(&self, config: &Config) -> Result<(), ApplyError> {
+        let unknown: Vec<String> = self
+            .ids
+            .iter()
+            .filter(|id| {
+                !config.specific.iter().any(|(_, file_config)| {
+                    file_config.locations.iter().any(|loc| loc.id.as_ref() == Some(id))
+                })
+            })
+            .cloned()
+            .collect();
+        match unknown.is_empty() {
+            true => Ok(()),
+            false => Err(ApplyError::UnknownIds(unknown)),
+        }
+    }
+
+    pub fn matches(&self, file_format: &str, loc: &Location) -> bool {
+        let variables = location_variables(loc);
+
+        (self.tags.is_empty() || loc.tags.iter().any(|t| self.tags.contains(t)))
+            && !loc.tags.iter().any(|t| self.exclude_tags.contains(t))
+            && (self.ids.is_empty() || loc.id.as_ref().is_some_and(|id| self.ids.contains(id)))
+            && (self.variables.is_empty() || variables.iter().any(|v| self.variables.contains(v)))
+            && (self.formats.is_empty() || self.formats.iter().any(|f| f == file_format))
+    }
+
+    pub fn matches_file(&self, file: &Path) -> bool {
+        if self.files.is_empty() {
+            return true;
+        }
+
+        let canonical = file.canonicalize().ok();
+        self.files.iter().any(|selected| {
+            selected == file || (canonical.is_some() && selected.canonicalize().ok() == canonical)
+        })
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::Transform;
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+
+/// Everything except the RFC 3986 unreserved characters gets percent-encoded
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+impl Transform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transform::Trim => "trim",
+            Transform::Lower => "lower",
+            Transform::Upper => "upper",
+            Transform::Base64 => "base64",
+            Transform::Base64Decode => "base64-decode",
+            Transform::Hex => "hex",
+            Transform::HexDecode => "hex-decode",
+            Transform::Urlencode => "urlencode",
+            Transform::Sha256 => "sha256",
+            Transform::Bcrypt => "bcrypt",
+            Transform::JsonEscape => "json-escape",
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String> {
+        Ok(match self {
+            Transform::Trim => value.trim().to_string(),
+            Transform::Lower => value.to_lowercase(),
+            Transform::Upper => value.to_uppercase(),
+            Transform::Base64 => STANDARD.encode(value),
+            Transform::Base64Decode => String::from_utf8(STANDARD.decode(value.trim())?)?,
+            Transform::Hex => hex::encode(value),
+            Transform::HexDecode => String::from_utf8(hex::decode(value.trim())?)?,
+            Transform::Urlencode => utf8_percent_encode(value, URL_ENCODE_SET).to_string(),
+            Transform::Sha256 => hex::encode(Sha256::digest(value)),
+            Transform::Bcrypt => bcrypt::hash(value, bcrypt::DEFAULT_COST)?,
+            Transform::JsonEscape => {
+                let quoted = serde_json::to_string(value)?;
+                quoted[1..quoted.len() - 1].to_string()
+            }
+        })
+    }
+}
+
+/// Runs `value` through every transform in order.
+pub fn apply_all(transforms: &[Transform], value: String) -> Result<String> {
+    transforms.iter().try_fold(value, |value, transform| {
+        transform
+            .apply(&value)
+            .map_err(|e| anyhow!("Transform {} failed: {}", transform.name(), e))
+    })
+}
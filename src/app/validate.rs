@@ -0,0 +1,354 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::app::{find_node, is_active, lookup_variable, missing_variables, Selection};
+use crate::config::{schema, Config, Location, Origin, Segment, SourceFile};
+use crate::env::{parser, read_secret, Resolver};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found in the config, pointing at where it is defined when that is known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    #[serde(flatten)]
+    pub origin: Option<Origin>,
+}
+
+impl Diagnostic {
+    fn error(message: String, origin: Option<&Origin>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message,
+            origin: origin.cloned(),
+        }
+    }
+
+    fn warning(message: String, origin: Option<&Origin>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message,
+            origin: origin.cloned(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(origin) = &self.origin {
+            write!(f, "{}: ", origin)?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Checks the config against its target files and the environment, beyond what loading does.
+///
+/// # Notes
+/// * Every target file must exist and contain every node, and no node may be written twice with different values
+/// * Locations without files or with empty nodes are reported, `apply` skips them silently
+/// * Unknown keys are reported with the closest known key
+/// * Variables which are set in no environment source and have no default are warnings
+pub async fn validate(config: &Config, env: &Resolver) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let schema = schema::generate().to_value();
+    for path in config.include_tree.files() {
+        unknown_keys(path, &schema, &mut diagnostics).await;
+    }
+
+    for name in missing_variables(config, env, &Selection::default()) {
+        let message = env.source_error(&name).map_or_else(
+            || format!("Required variable `{}` is not set", name),
+            |e| format!("Cannot read required variable `{}`: {}", name, e),
+        );
+        diagnostics.push(Diagnostic::error(message, None));
+    }
+
+    let mut writers: HashMap<(PathBuf, &str), &Location> = HashMap::new();
+    let mut unset = HashSet::new();
+
+    for (file_format, file_config) in config.specific.iter() {
+        for loc in &file_config.locations {
+            let origin = loc.origin.as_ref();
+            let label = loc.label();
+
+            if loc.file.is_empty() {
+                diagnostics.push(Diagnostic::error(
+                    format!("Location for `{}` has no target file", label),
+                    origin,
+                ));
+            }
+            if loc.node.is_empty() {
+                diagnostics.push(Diagnostic::error(
+                    format!("Location for `{}` has no target node", label),
+                    origin,
+                ));
+            }
+            if loc.node.iter().any(|node| node.trim().is_empty()) {
+                diagnostics.push(Diagnostic::error(
+                    format!("Location for `{}` has an empty node", label),
+                    origin,
+                ));
+            }
+
+            if !is_active(config, env, loc) {
+                continue;
+            }
+
+            let variables = match (&loc.value, &loc.variable_file) {
+                (Some(template), _) => parser::unresolved(template, |name| lookup_variable(config, env, name)),
+                (None, Some(_)) => Vec::new(),
+                (None, None) => vec![loc.variable.clone()],
+            };
+            for name in variables {
+                if lookup_variable(config, env, &name).is_none() && unset.insert(name.clone()) {
+                    if let Some(e) = env.source_error(&name) {
+                        diagnostics.push(Diagnostic::error(
+                            format!("Cannot read variable `{}`: {}", name, e),
+                            origin,
+                        ));
+                        continue;
+                    }
+                    let message = env.secret_error(&name).unwrap_or_else(|| {
+                        format!("Variable `{}` is not set in any environment source", name)
+                    });
+                    diagnostics.push(Diagnostic::warning(message, origin));
+                }
+            }
+            if let Some(Err(e)) = loc.variable_file.as_deref().map(read_secret) {
+                diagnostics.push(Diagnostic::warning(
+                    format!("Cannot read variable file `{}`: {}", label, e),
+                    origin,
+                ));
+            }
+
+            let paths: Vec<String> = loc
+                .file
+                .iter()
+                .map(|f| f.to_string_lossy().to_string())
+                .chain(loc.node.iter().cloned())
+                .collect();
+            let lookup = |name: &str| lookup_variable(config, env, name);
+            let mut names = Vec::new();
+            for name in paths.iter().flat_map(|p| parser::unresolved_braced(p, lookup)) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            if !names.is_empty() {
+                diagnostics.push(Diagnostic::error(
+                    format!(
+                        "Cannot resolve variables in the file or node paths of `{}`: {}",
+                        label,
+                        names.join(", ")
+                    ),
+                    origin,
+                ));
+                continue;
+            }
+
+            for file in &loc.file {
+                let content = match fs::read_to_string(file).await {
+                    Ok(content) => content,
+                    Err(_) if !file.exists() => {
+                        diagnostics.push(Diagnostic::error(
+                            format!("Target file {} does not exist", file.display()),
+                            origin,
+                        ));
+                        continue;
+                    }
+                    Err(e) => {
+                        diagnostics.push(Diagnostic::error(
+                            format!("Cannot read target file {}: {}", file.display(), e),
+                            origin,
+                        ));
+                        continue;
+                    }
+                };
+
+                for node in loc.node.iter().filter(|node| !node.trim().is_empty()) {
+                    match find_node(file_format, &content, node) {
+                        Ok(Some(_)) => {}
+                        Ok(None) => diagnostics.push(Diagnostic::error(
+                            format!("Node `{}` not found in {}", node, file.display()),
+                            origin,
+                        )),
+                        Err(e) => diagnostics.push(Diagnostic::error(
+                            format!(
+                                "Node `{}` in {} cannot be written: {}",
+                                node,
+                                file.display(),
+                                e
+                            ),
+                            origin,
+                        )),
+                    }
+
+                    let key = (
+                        file.canonicalize().unwrap_or_else(|_| file.clone()),
+                        node.as_str(),
+                    );
+                    match writers.get(&key) {
+                        Some(first) if first.label() != label => {
+                            diagnostics.push(Diagnostic::error(
+                                format!(
+                                    "Node `{}` in {} is written by both `{}` and `{}`{}",
+                                    node,
+                                    file.display(),
+                                    first.label(),
+                                    label,
+                                    first
+                                        .origin
+                                        .as_ref()
+                                        .map(|o| format!(" (first defined at {})", o))
+                                        .unwrap_or_default()
+                                ),
+                                origin,
+                            ));
+                        }
+                        Some(_) => {}
+                        None => {
+                            writers.insert(key, loc);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Reports keys of a config file which don't match any config field.
+async fn unknown_keys(path: &Path, schema: &Value, diagnostics: &mut Vec<Diagnostic>) {
+    let Ok(content) = fs::read_to_string(path).await else {
+        return;
+    };
+
+    let mut ignored = Vec::new();
+    let parsed: Result<Config, _> =
+        serde_ignored::deserialize(toml::Deserializer::new(&content), |path| {
+            let mut segments = Vec::new();
+            collect_segments(&path, &mut segments);
+            ignored.push(segments);
+        });
+    // Errors were already reported while loading the merged config
+    if parsed.is_err() {
+        return;
+    }
+
+    let Ok(source) = SourceFile::parse(path, content) else {
+        return;
+    };
+    for segments in ignored {
+        let Some((Segment::Key(key), parent)) = segments.split_last() else {
+            continue;
+        };
+        let message = match suggest(schema, parent, key) {
+            Some(known) => format!("Unknown key `{}`, did you mean `{}`?", key, known),
+            None => format!("Unknown key `{}`", key),
+        };
+        diagnostics.push(Diagnostic::warning(
+            message,
+            source.origin(&segments).as_ref(),
+        ));
+    }
+}
+
+fn collect_segments(path: &serde_ignored::Path, segments: &mut Vec<Segment>) {
+    match path {
+        serde_ignored::Path::Root => {}
+        serde_ignored::Path::Seq { parent, index } => {
+            collect_segments(parent, segments);
+            segments.push(Segment::Index(*index));
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            collect_segments(parent, segments);
+            segments.push(Segment::Key(key.clone()));
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => collect_segments(parent, segments),
+    }
+}
+
+/// Returns the known key closest to `key` in the table at `path`, looked up in the config schema.
+fn suggest(schema: &Value, path: &[Segment], key: &str) -> Option<String> {
+    let defs = &schema["$defs"];
+    let mut node = schema;
+    for segment in path {
+        node = match segment {
+            Segment::Key(key) => {
+                let table = resolve(node, defs, "properties")
+                    .or_else(|| resolve(node, defs, "additionalProperties"))?;
+                table["properties"]
+                    .get(key)
+                    .or_else(|| table.get("additionalProperties"))?
+            }
+            Segment::Index(_) => resolve(node, defs, "items")?.get("items")?,
+        };
+    }
+
+    resolve(node, defs, "properties")?["properties"]
+        .as_object()?
+        .keys()
+        .map(|known| (strsim::levenshtein(key, known), known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, known)| known.clone())
+}
+
+/// Follows `$ref`s and `anyOf` variants to the first schema having the keyword.
+fn resolve<'a>(node: &'a Value, defs: &'a Value, keyword: &str) -> Option<&'a Value> {
+    if let Some(reference) = node.get("$ref").and_then(|r| r.as_str()) {
+        let name = reference.strip_prefix("#/$defs/")?;
+        return resolve(defs.get(name)?, defs, keyword);
+    }
+    if let Some(variants) = node.get("anyOf").and_then(|v| v.as_array()) {
+        return variants.iter().find_map(|v| resolve(v, defs, keyword));
+    }
+    node.get(keyword).map(|_| node)
+}
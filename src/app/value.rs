@@ -0,0 +1,175 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::ValueType;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static NUMBER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^-?\d+(\.\d+)?([eE][+-]?\d+)?$").unwrap());
+static FLOAT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[+-]?\d+(\.\d+)?([eE][+-]?\d+)?$").unwrap());
+static DATETIME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])([Tt ]([01]\d|2[0-3]):[0-5]\d:[0-5]\d(\.\d+)?([Zz]|[+-]([01]\d|2[0-3]):[0-5]\d)?)?$",
+    )
+    .unwrap()
+});
+
+/// Formats a value as a literal for the given file format.
+///
+/// # Notes
+/// * Without a type, values that are already a valid number literal for the format are
+///   written unchanged and everything else, e.g. `007`, is written as a string
+/// * With a type, the value is validated and converted to the format's native literal
+pub fn parse_variable(file_type: &str, env: &str, value_type: Option<ValueType>) -> Result<String> {
+    let Some(value_type) = value_type else {
+        if is_number_literal(file_type, env) {
+            return Ok(env.to_string());
+        }
+        return Ok(string(file_type, env));
+    };
+
+    match value_type {
+        ValueType::Raw => Ok(env.to_string()),
+        ValueType::String => Ok(string(file_type, env)),
+        ValueType::Integer => integer(file_type, env),
+        ValueType::Float => float(file_type, env),
+        ValueType::Bool => bool(env),
+        ValueType::Null => null(file_type, env),
+        ValueType::Datetime => datetime(file_type, env),
+    }
+}
+
+/// Whether `value` can be written as is as a number of the format.
+fn is_number_literal(file_type: &str, value: &str) -> bool {
+    if !NUMBER_RE.is_match(value) {
+        return false;
+    }
+
+    // `007` stays a string instead of an invalid or octal number
+    let unsigned = value.trim_start_matches('-');
+    let int_part = &unsigned[..unsigned.find(['.', 'e', 'E']).unwrap_or(unsigned.len())];
+    if int_part.len() > 1 && int_part.starts_with('0') {
+        return false;
+    }
+
+    if int_part.len() == unsigned.len() {
+        file_type != "toml" || value.parse::<i64>().is_ok()
+    } else {
+        value.parse::<f64>().is_ok_and(|f| f.is_finite())
+    }
+}
+
+fn string(file_type: &str, value: &str) -> String {
+    match file_type {
+        // JSON string escapes are also valid in YAML & TOML double quoted strings and in HOCON
+        "yaml" | "json" | "toml" | "hocon" => serde_json::to_string(value).unwrap(),
+        "xml" => value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+        _ => value.to_string(),
+    }
+}
+
+fn integer(file_type: &str, value: &str) -> Result<String> {
+    let trimmed = value.trim();
+    let (sign, digits) = match trimmed.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("expected an integer"));
+    }
+
+    // Leading zeros are invalid in JSON & TOML and mean octal in YAML 1.1
+    let digits = digits.trim_start_matches('0');
+    let literal = if digits.is_empty() {
+        "0".to_string()
+    } else {
+        format!("{}{}", sign, digits)
+    };
+
+    if file_type == "toml" && literal.parse::<i64>().is_err() {
+        return Err(anyhow!("integer is out of range for TOML"));
+    }
+    Ok(literal)
+}
+
+fn float(file_type: &str, value: &str) -> Result<String> {
+    let trimmed = value.trim();
+    if !FLOAT_RE.is_match(trimmed)
+        || !trimmed.parse::<f64>().is_ok_and(|f| f.is_finite())
+    {
+        return Err(anyhow!("expected a float"));
+    }
+
+    let unsigned = trimmed.trim_start_matches(['+', '-']);
+    let sign = if trimmed.starts_with('-') { "-" } else { "" };
+    let int_len = unsigned.find(['.', 'e', 'E']).unwrap_or(unsigned.len());
+    let int_part = unsigned[..int_len].trim_start_matches('0');
+    let int_part = if int_part.is_empty() { "0" } else { int_part };
+    let mut rest = unsigned[int_len..].to_string();
+
+    // A number without a fraction reads as an integer in these formats
+    if !rest.starts_with('.') && matches!(file_type, "yaml" | "toml" | "hocon") {
+        rest.insert_str(0, ".0");
+    }
+    Ok(format!("{}{}{}", sign, int_part, rest))
+}
+
+fn bool(value: &str) -> Result<String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok("true".to_string()),
+        "false" | "no" | "off" | "0" => Ok("false".to_string()),
+        _ => Err(anyhow!("expected a boolean")),
+    }
+}
+
+fn null(file_type: &str, value: &str) -> Result<String> {
+    if !matches!(value.trim().to_lowercase().as_str(), "" | "null" | "~") {
+        return Err(anyhow!("expected an empty value or null"));
+    }
+
+    match file_type {
+        "yaml" | "json" | "hocon" => Ok("null".to_string()),
+        "toml" => Err(anyhow!("TOML has no null value")),
+        _ => Ok(String::new()),
+    }
+}
+
+fn datetime(file_type: &str, value: &str) -> Result<String> {
+    let trimmed = value.trim();
+    if !DATETIME_RE.is_match(trimmed) {
+        return Err(anyhow!("expected an RFC 3339 date or datetime"));
+    }
+
+    match file_type {
+        // TOML & YAML have native datetime literals, JSON & HOCON store them as strings
+        "yaml" | "toml" => Ok(trimmed.to_string()),
+        _ => Ok(string(file_type, trimmed)),
+    }
+}
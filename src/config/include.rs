@@ -0,0 +1,276 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{migrate, ConfigError, Origin, SourceFile};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use toml::{Table, Value};
+
+/// A loaded config file and the files it pulled in through `extends`/`include`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IncludeTree {
+    pub path: PathBuf,
+    pub children: Vec<IncludeTree>,
+    /// Set when the file had already been merged through another include and was skipped.
+    pub duplicate: bool,
+    /// The file's config format version, 0 when it has no `version`
+    pub version: u32,
+    /// Glob patterns of the file's `extends`/`include` which matched no files
+    pub unmatched: Vec<String>,
+}
+
+/// Where the entries of each merged `locations` array came from, in merge order.
+pub type LocationOrigins = BTreeMap<Vec<String>, Vec<Origin>>;
+
+impl IncludeTree {
+    /// The files which were actually merged, in the order they were loaded.
+    pub fn files(&self) -> Vec<&Path> {
+        self.nodes().into_iter().map(|node| node.path.as_path()).collect()
+    }
+
+    /// The merged files which don't declare a `version`.
+    pub fn unversioned(&self) -> Vec<&Path> {
+        self.nodes()
+            .into_iter()
+            .filter(|node| node.version == 0)
+            .map(|node| node.path.as_path())
+            .collect()
+    }
+
+    /// The glob patterns which matched no files, along with the file declaring them.
+    pub fn unmatched(&self) -> Vec<(&Path, &str)> {
+        self.nodes()
+            .into_iter()
+            .flat_map(|node| {
+                node.unmatched
+                    .iter()
+                    .map(|pattern| (node.path.as_path(), pattern.as_str()))
+            })
+            .collect()
+    }
+
+    fn nodes(&self) -> Vec<&IncludeTree> {
+        let mut nodes = Vec::new();
+        for child in self.children.iter().filter(|c| !c.duplicate) {
+            nodes.extend(child.nodes());
+        }
+        nodes.push(self);
+        nodes
+    }
+
+    fn write_children(&self, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i == self.children.len() - 1;
+            write!(
+                f,
+                "\n{}{}{}",
+                indent,
+                if last { "└── " } else { "├── " },
+                child.path.display()
+            )?;
+            if child.duplicate {
+                write!(f, " (already included)")?;
+            }
+            child.write_children(
+                f,
+                &format!("{}{}", indent, if last { "    " } else { "│   " }),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for IncludeTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        self.write_children(f, "")
+    }
+}
+
+/// Loads a config file and recursively merges everything it includes.
+///
+/// # Notes
+/// * `extends` entries are merged first, then `include` entries, then the file itself
+/// * Include paths resolve relative to the including file and may contain glob patterns
+/// * Later files win on scalar values, `locations` arrays are concatenated
+/// * A file included a second time is skipped, a file including one of its ancestors is an error
+pub async fn load_merged(
+    path: &Path,
+) -> Result<(Table, IncludeTree, LocationOrigins), ConfigError> {
+    let root = fs::canonicalize(path).await?;
+    let mut stack = vec![root.clone()];
+    let mut seen = HashSet::from([root]);
+
+    load_file(path, &mut stack, &mut seen).await
+}
+
+async fn load_file(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    seen: &mut HashSet<PathBuf>,
+) -> Result<(Table, IncludeTree, LocationOrigins), ConfigError> {
+    let content = fs::read_to_string(path).await?;
+    let table: Table = toml::from_str(&content).map_err(|e| {
+        ConfigError::ParseError(format!("TOML parsing error in {}: {}", path.display(), e))
+    })?;
+    let source = SourceFile::parse(path, content).map_err(|e| {
+        ConfigError::ParseError(format!("TOML parsing error in {}: {}", path.display(), e))
+    })?;
+    let version = migrate::read_version(path, source.item("version"))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut unmatched = Vec::new();
+    let mut includes = include_paths(&table, "extends", base_dir, &mut unmatched)?;
+    includes.extend(include_paths(&table, "include", base_dir, &mut unmatched)?);
+
+    let mut merged = Table::new();
+    let mut children = Vec::new();
+    let mut origins = LocationOrigins::new();
+
+    for include in includes {
+        let canonical = fs::canonicalize(&include)
+            .await
+            .map_err(|_| ConfigError::NotFound(include.to_string_lossy().to_string()))?;
+
+        if let Some(start) = stack.iter().position(|p| *p == canonical) {
+            let cycle: Vec<String> = stack[start..]
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(ConfigError::IncludeCycle(cycle.join(" -> ")));
+        }
+
+        if !seen.insert(canonical.clone()) {
+            children.push(IncludeTree {
+                path: include,
+                children: Vec::new(),
+                duplicate: true,
+                version: 0,
+                unmatched: Vec::new(),
+            });
+            continue;
+        }
+
+        stack.push(canonical);
+        let (mut child_table, child_tree, child_origins) =
+            Box::pin(load_file(&include, stack, seen)).await?;
+        stack.pop();
+
+        // Only the root file's own include lists survive into the merged config
+        child_table.remove("extends");
+        child_table.remove("include");
+        merge(&mut merged, child_table);
+        merge_origins(&mut origins, child_origins);
+        children.push(child_tree);
+    }
+
+    merge(&mut merged, table);
+    merge_origins(&mut origins, source.location_origins());
+
+    Ok((
+        merged,
+        IncludeTree {
+            path: path.to_path_buf(),
+            children,
+            duplicate: false,
+            version,
+            unmatched,
+        },
+        origins,
+    ))
+}
+
+/// Resolves the entries of `extends` or `include`, collecting glob patterns which match nothing
+/// into `unmatched`.
+fn include_paths(
+    table: &Table,
+    key: &str,
+    base_dir: &Path,
+    unmatched: &mut Vec<String>,
+) -> Result<Vec<PathBuf>, ConfigError> {
+    let entries = match table.get(key) {
+        None => return Ok(Vec::new()),
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|v| {
+                v.as_str().ok_or_else(|| {
+                    ConfigError::ParseError(format!("`{}` entries must be strings", key))
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => {
+            return Err(ConfigError::ParseError(format!(
+                "`{}` must be a string or an array of strings",
+                key
+            )))
+        }
+    };
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let joined = base_dir.join(entry);
+        if !entry.contains(['*', '?', '[']) {
+            paths.push(joined);
+            continue;
+        }
+
+        let pattern = joined.to_string_lossy();
+        let mut matches = glob::glob(&pattern)
+            .map_err(|e| ConfigError::InvalidInclude(format!("{}: {}", entry, e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ConfigError::InvalidInclude(format!("{}: {}", entry, e)))?;
+        if matches.is_empty() {
+            unmatched.push(entry.to_string());
+        }
+        matches.sort();
+        paths.extend(matches);
+    }
+
+    Ok(paths)
+}
+
+pub(super) fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(incoming)) => merge(existing, incoming),
+            (Some(Value::Array(existing)), Value::Array(incoming)) if key == "locations" => {
+                existing.extend(incoming)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Mirrors `merge` concatenating `locations` arrays.
+fn merge_origins(base: &mut LocationOrigins, overlay: LocationOrigins) {
+    for (path, origins) in overlay {
+        base.entry(path).or_default().extend(origins);
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{include, ConfigError};
+use std::path::{Path, PathBuf};
+use toml_edit::{value, DocumentMut, Item};
+
+/// The config format version this build reads and writes.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// A rewrite of a config document from one version to the next.
+struct Step {
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut DocumentMut),
+}
+
+/// Every migration, in order. Unversioned files are version 0.
+const STEPS: &[Step] = &[Step {
+    from: 0,
+    description: "Add the `version` field",
+    apply: |_| {},
+}];
+
+/// A config file rewritten to the current version.
+#[derive(Debug)]
+pub struct Migration {
+    pub path: PathBuf,
+    pub from: u32,
+    /// Descriptions of the steps which were applied, empty when the file is up to date
+    pub steps: Vec<&'static str>,
+    pub content: String,
+}
+
+/// Migrates the config file and every file it includes, without writing anything.
+pub async fn migrate(path: &Path) -> Result<Vec<Migration>, ConfigError> {
+    let (_, include_tree, _) = include::load_merged(path).await?;
+
+    let mut migrations = Vec::new();
+    for file in include_tree.files() {
+        let content = tokio::fs::read_to_string(file).await?;
+        migrations.push(migrate_content(file, &content)?);
+    }
+    Ok(migrations)
+}
+
+/// Rewrites a config document to the current version, keeping its comments and layout.
+pub fn migrate_content(path: &Path, content: &str) -> Result<Migration, ConfigError> {
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))?;
+    let from = read_version(path, document.get("version"))?;
+
+    let mut steps = Vec::new();
+    for step in STEPS.iter().filter(|step| step.from >= from) {
+        (step.apply)(&mut document);
+        steps.push(step.description);
+    }
+
+    let content = match (steps.is_empty(), document.contains_key("version")) {
+        (true, _) => content.to_string(),
+        (false, true) => {
+            document["version"] = value(i64::from(CURRENT_VERSION));
+            document.to_string()
+        }
+        (false, false) => prepend_version(&document.to_string()),
+    };
+
+    Ok(Migration {
+        path: path.to_path_buf(),
+        from,
+        steps,
+        content,
+    })
+}
+
+/// Adds the `version` key at the top of a document, below a leading comment block such as a
+/// `#:schema` directive.
+fn prepend_version(content: &str) -> String {
+    let header: String = content
+        .split_inclusive('\n')
+        .take_while(|line| line.trim_start().starts_with('#'))
+        .collect();
+    let rest = &content[header.len()..];
+
+    let separator = match rest.starts_with('\n') || rest.starts_with("\r\n") || rest.is_empty() {
+        true => "",
+        false => "\n",
+    };
+    format!(
+        "{}version = {}\n{}{}",
+        header, CURRENT_VERSION, separator, rest
+    )
+}
+
+/// Reads the `version` of a config file, 0 when unset.
+///
+/// Fails for versions newer than this build understands.
+pub(super) fn read_version(path: &Path, version: Option<&Item>) -> Result<u32, ConfigError> {
+    let Some(version) = version else {
+        return Ok(0);
+    };
+
+    let version = version
+        .as_integer()
+        .and_then(|v| u32::try_from(v).ok())
+        .ok_or_else(|| {
+            ConfigError::ParseError(format!(
+                "{}: `version` must be a positive integer",
+                path.display()
+            ))
+        })?;
+    if version > CURRENT_VERSION {
+        return Err(ConfigError::UnsupportedVersion(format!(
+            "{} uses version {}, but this build only supports up to version {}",
+            path.display(),
+            version,
+            CURRENT_VERSION
+        )));
+    }
+    Ok(version)
+}
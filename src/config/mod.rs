@@ -22,10 +22,26 @@
  * SOFTWARE.
  */
 
+mod include;
+pub mod migrate;
+mod placeholder;
+mod profile;
+pub mod schema;
+mod show;
+mod source;
+
+pub use include::IncludeTree;
+pub use placeholder::Placeholder;
+pub use profile::Profile;
+pub use show::Format;
+pub use source::{Origin, Segment, SourceFile};
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 use thiserror::Error;
-use tokio::fs;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -35,24 +51,244 @@ pub enum ConfigError {
     ParseError(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("Include cycle detected: {0}")]
+    IncludeCycle(String),
+    #[error("Invalid include: {0}")]
+    InvalidInclude(String),
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
+    #[error("Unknown location id: {0}")]
+    UnknownLocation(String),
+    #[error("Duplicate location id: {0}")]
+    DuplicateId(String),
+    #[error("Invalid location: {0}")]
+    InvalidLocation(String),
+    #[error("Config serialization error: {0}")]
+    SerializeError(String),
+    #[error("Unsupported config version: {0}")]
+    UnsupportedVersion(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+/// Tells Env Applier which nodes to change in which files.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 pub struct Config {
+    /// Version of the config format, see `ea config migrate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(
+        deserialize_with = "deserialize_paths",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(schema_with = "schema::string_or_list")]
+    pub extends: Vec<PathBuf>,
+    #[serde(
+        deserialize_with = "deserialize_paths",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(schema_with = "schema::string_or_list")]
+    pub include: Vec<PathBuf>,
     #[serde(default)]
     pub environment: Environment,
     #[serde(default)]
     pub specific: Specific,
+    #[serde(default, skip_serializing_if = "Sources::is_empty")]
+    pub sources: Sources,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// The files merged into this config, populated by `load_config`
+    #[serde(skip)]
+    pub include_tree: IncludeTree,
+    /// The profile applied through `apply_profile`, if any
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 pub struct Environment {
     #[serde(default = "default_prefix")]
     pub prefix: String,
     #[serde(default = "default_suffix")]
     pub suffix: String,
     #[serde(default)]
-    pub variables: Vec<String>,
+    pub variables: Vec<Variable>,
+    /// Policy for locations whose variable is missing and which don't set their own
+    #[serde(default)]
+    pub on_missing: OnMissing,
+    /// Directories of secret files named after their variable, `/run/secrets` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_dirs: Option<Vec<PathBuf>>,
+    /// Env files loaded after the `.env` files, later files take precedence
+    #[serde(
+        deserialize_with = "deserialize_paths",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(schema_with = "schema::string_or_list")]
+    pub env_files: Vec<PathBuf>,
+}
+
+/// What `apply` does with a location whose variable cannot be resolved.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnMissing {
+    /// Leave the nodes untouched
+    #[default]
+    Skip,
+    /// Abort before anything is written
+    Error,
+    /// Write the location's `fallback` value
+    Default,
+    /// Write the same placeholder `deapply` would write
+    Placeholder,
+}
+
+impl OnMissing {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnMissing::Skip => "skip",
+            OnMissing::Error => "error",
+            OnMissing::Default => "default",
+            OnMissing::Placeholder => "placeholder",
+        }
+    }
+}
+
+/// A variable that must be resolvable before `apply` writes anything.
+///
+/// Accepts either a plain name or a table with a `name`, `description` and `default`.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq)]
+#[schemars(with = "VariableInput")]
+pub struct Variable {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Value used when the variable is not set in the environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+
+#[derive(Deserialize, JsonSchema)]
+struct VariableTable {
+    name: String,
+    description: Option<String>,
+    /// Value used when the variable is not set in the environment
+    default: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum VariableInput {
+    Name(String),
+    Table(VariableTable),
+}
+
+impl<'de> Deserialize<'de> for Variable {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match VariableInput::deserialize(deserializer)? {
+            VariableInput::Name(name) => Variable {
+                name,
+                description: None,
+                default: None,
+            },
+            VariableInput::Table(t) => Variable {
+                name: t.name,
+                description: t.description,
+                default: t.default,
+            },
+        })
+    }
+}
+
+impl Environment {
+    /// The secret directories to read, defaulting to Docker's `/run/secrets`.
+    pub fn secret_dirs(&self) -> Vec<PathBuf> {
+        self.secret_dirs
+            .clone()
+            .unwrap_or_else(|| vec![PathBuf::from("/run/secrets")])
+    }
+}
+
+/// Remote stores variables are read from besides the env files.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+pub struct Sources {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<VaultSource>,
+    /// Commands printing variables, such as `pass` or `op`, keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cmd: BTreeMap<String, CommandSource>,
+}
+
+impl Sources {
+    pub fn is_empty(&self) -> bool {
+        self.vault.is_none() && self.cmd.is_empty()
+    }
+}
+
+/// A command whose standard output provides variables, e.g. `pass show prod/db`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CommandSource {
+    /// The program and its arguments, run without a shell
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub format: CommandFormat,
+    /// Variable set to the output in text format, defaults to the source's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// Variables read from JSON output, mapped to a key or a JSON pointer such as
+    /// `/fields/0/value`, required in JSON format
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+    /// Seconds to wait for the command
+    #[serde(default = "default_command_timeout")]
+    pub timeout: u64,
+}
+
+/// How the output of a command source is read.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandFormat {
+    /// The whole output is one value, without its trailing newline
+    #[default]
+    Text,
+    /// The output is a JSON object mapping several variables
+    Json,
+}
+
+/// A HashiCorp Vault KV v2 secrets engine.
+///
+/// The token is read from `VAULT_TOKEN`, or obtained through AppRole from `VAULT_ROLE_ID`
+/// and `VAULT_SECRET_ID`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VaultSource {
+    /// Address of the Vault server, `VAULT_ADDR` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Mount path of the KV v2 engine
+    #[serde(default = "default_vault_mount")]
+    pub mount: String,
+    /// Secret whose keys are exposed as variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Request timeout in seconds
+    #[serde(default = "default_vault_timeout")]
+    pub timeout: u64,
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_string()
+}
+fn default_vault_timeout() -> u64 {
+    10
+}
+fn default_command_timeout() -> u64 {
+    10
 }
 
 fn default_prefix() -> String {
@@ -62,7 +298,7 @@ fn default_suffix() -> String {
     "%".to_string()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 pub struct Specific {
     #[serde(default)]
     pub json: FileTypeConfig,
@@ -90,22 +326,44 @@ impl Specific {
         ]
             .into_iter()
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=(&'static str, &mut FileTypeConfig)> {
+        vec![
+            ("json", &mut self.json),
+            ("toml", &mut self.toml),
+            ("yaml", &mut self.yaml),
+            ("properties", &mut self.properties),
+            ("xml", &mut self.xml),
+            ("hocon", &mut self.hocon),
+        ]
+            .into_iter()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 pub struct FileTypeConfig {
     #[serde(default)]
     pub locations: Vec<Location>,
+    /// Placeholder written by `deapply` for this format's locations, instead of the global prefix/suffix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Placeholder>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct Location {
+    /// Optional identifier used to reference this location, e.g. from profiles
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Labels used to select locations from the command line
+    #[serde(default)]
+    pub tags: Vec<String>,
     #[serde(
         deserialize_with = "deserialize_paths",
         default,
         alias = "files",  // Add alias for plural form
         rename = "file"   // But keep original name in struct
     )]
+    #[schemars(schema_with = "schema::string_or_list")]
     pub file: Vec<PathBuf>,
     #[serde(
         deserialize_with = "deserialize_nodes",
@@ -113,16 +371,215 @@ pub struct Location {
         alias = "nodes",  // Add alias for plural form
         rename = "node"   // But keep original name in struct
     )]
+    #[schemars(schema_with = "schema::string_or_list")]
     pub node: Vec<String>,
 
+    /// The variable whose value is written, either this or `value` must be set
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub variable: String,
+    /// Template written instead of a single variable, e.g. `jdbc:mysql://${DB_HOST}:${DB_PORT}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// File whose contents are written instead of a variable, e.g. `/run/secrets/db_pass`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<String>,
+    /// Placeholder written by `deapply`, overriding the one of the file format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Placeholder>,
+    /// Overrides `environment.on_missing` for this location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_missing: Option<OnMissing>,
+    /// Value written when the variable is missing and `on_missing = "default"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    /// Transforms applied in order to the resolved value before it is written
+    #[serde(default)]
+    pub transform: Vec<Transform>,
+    /// The literal type written to the file, guessed from the value when unset
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<ValueType>,
+    /// Condition deciding whether this location is applied, deapplied and listed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<When>,
 
     #[serde(default, rename = "override")]
     pub override_settings: OverrideSettings,
+    /// Where the location is defined, populated by `load_config`
+    #[serde(skip)]
+    pub origin: Option<Origin>,
+}
+
+impl Config {
+    /// Ensures every location writes exactly one of `variable`, `value` or `variable_file`.
+    pub fn check_locations(&self) -> Result<(), ConfigError> {
+        for (file_format, file_config) in self.specific.iter() {
+            for loc in &file_config.locations {
+                let sources = [!loc.variable.is_empty(), loc.value.is_some(), loc.variable_file.is_some()];
+                match sources.iter().filter(|set| **set).count() {
+                    0 => {
+                        return Err(ConfigError::InvalidLocation(format!(
+                            "{} location for {:?} needs a `variable`, a `value` or a `variable_file`",
+                            file_format, loc.file
+                        )))
+                    }
+                    1 => {}
+                    _ => {
+                        return Err(ConfigError::InvalidLocation(format!(
+                            "{} location for {} sets more than one of `variable`, `value` and `variable_file`",
+                            file_format,
+                            loc.label()
+                        )))
+                    }
+                }
+
+                if let Some(when) = &loc.when {
+                    when.check().map_err(|e| {
+                        ConfigError::InvalidLocation(format!("{} location for {}: {}", file_format, loc.label(), e))
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Ensures location ids are unique across all file formats.
+    pub fn check_ids(&self) -> Result<(), ConfigError> {
+        let mut seen = HashSet::new();
+        for (_, file_config) in self.specific.iter() {
+            for id in file_config
+                .locations
+                .iter()
+                .filter_map(|loc| loc.id.as_ref())
+            {
+                if !seen.insert(id) {
+                    return Err(ConfigError::DuplicateId(id.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records where each location, including those of profiles, is defined.
+    fn set_origins(&mut self, origins: &include::LocationOrigins) {
+        let mut targets = vec![(vec!["specific".to_string()], &mut self.specific)];
+        for (name, profile) in self.profiles.iter_mut() {
+            targets.push((
+                vec!["profiles".to_string(), name.clone(), "specific".to_string()],
+                &mut profile.specific,
+            ));
+        }
+
+        for (path, specific) in targets {
+            for (file_format, file_config) in specific.iter_mut() {
+                let mut path = path.clone();
+                path.push(file_format.to_string());
+                let Some(entries) = origins.get(&path) else {
+                    continue;
+                };
+                for (loc, origin) in file_config.locations.iter_mut().zip(entries) {
+                    loc.origin = Some(origin.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A condition on a location, every key that is set must hold.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct When {
+    /// Variable compared against `equals`, or required to be non-empty without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    /// Variable which must be set, even if empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exists: Option<String>,
+    /// Profile which must be active
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all: Option<Vec<When>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any: Option<Vec<When>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<When>>,
+}
+
+impl When {
+    fn check(&self) -> Result<(), String> {
+        if self.equals.is_some() && self.env.is_none() {
+            return Err("`equals` in a `when` condition requires `env`".to_string());
+        }
+        let nested = self.all.iter().chain(self.any.iter()).flatten();
+        nested.chain(self.not.as_deref()).try_for_each(When::check)
+    }
+}
+
+/// The type of literal written for a location's value, see `app::value`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    String,
+    #[serde(alias = "int")]
+    Integer,
+    Float,
+    #[serde(alias = "boolean")]
+    Bool,
+    Null,
+    /// Written exactly as resolved, without quoting or validation
+    Raw,
+    Datetime,
+}
+
+/// A built-in value transform, see `app::transform`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    Trim,
+    #[serde(alias = "lowercase")]
+    Lower,
+    #[serde(alias = "uppercase")]
+    Upper,
+    Base64,
+    Base64Decode,
+    Hex,
+    HexDecode,
+    #[serde(alias = "url-encode")]
+    Urlencode,
+    Sha256,
+    Bcrypt,
+    JsonEscape,
+}
+
+impl Location {
+    /// The variable, template or file written by this location, used in messages.
+    pub fn label(&self) -> Cow<'_, str> {
+        match (&self.value, &self.variable_file) {
+            (Some(template), _) => Cow::Borrowed(template),
+            (None, Some(file)) => file.to_string_lossy(),
+            (None, None) => Cow::Borrowed(&self.variable),
+        }
+    }
+
+    /// The name rendered into deapply placeholders.
+    ///
+    /// A `variable_file` only contributes its file name, keeping host paths such as
+    /// `/run/secrets` out of the usually committed target files.
+    pub fn placeholder_name(&self) -> Cow<'_, str> {
+        match (&self.value, &self.variable_file) {
+            (None, Some(file)) => file
+                .file_name()
+                .map_or_else(|| file.to_string_lossy(), |name| name.to_string_lossy()),
+            _ => self.label(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 pub struct OverrideSettings {
     #[serde(rename = "exemptApply")]
     pub exempt_apply: bool,
@@ -179,9 +636,14 @@ pub async fn load_config(custom_path: Option<PathBuf>) -> Result<Config, ConfigE
         ));
     }
 
-    let config_str = fs::read_to_string(&config_path).await?;
-    let config: Config = toml::from_str(&config_str)
+    let (merged, include_tree, origins) = include::load_merged(&config_path).await?;
+    let mut config: Config = toml::Value::Table(merged)
+        .try_into()
         .map_err(|e| ConfigError::ParseError(format!("TOML parsing error: {}", e)))?;
+    config.include_tree = include_tree;
+    config.set_origins(&origins);
+    config.check_ids()?;
+    config.check_locations()?;
 
     Ok(config)
 }
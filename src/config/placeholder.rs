@@ -0,0 +1,133 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A template for the placeholder `deapply` writes, e.g. `${{{variable}}}` for `${DB_PASS}`.
+///
+/// `{variable}` is replaced with the variable name, `{{` and `}}` produce literal braces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "String", into = "String")]
+pub struct Placeholder {
+    template: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Variable,
+}
+
+impl Placeholder {
+    /// A placeholder wrapping the variable name in a literal prefix and suffix.
+    pub fn affixed(prefix: &str, suffix: &str) -> Placeholder {
+        let escape = |text: &str| text.replace('{', "{{").replace('}', "}}");
+        Placeholder::try_from(format!("{}{{variable}}{}", escape(prefix), escape(suffix)))
+            .expect("escaped placeholder is always valid")
+    }
+
+    /// Renders the placeholder for a variable.
+    pub fn render(&self, variable: &str) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.as_str(),
+                Part::Variable => variable,
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<String> for Placeholder {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut token = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => token.push(c),
+                            None => return Err(format!(
+                                "Unclosed `{{` in placeholder `{}`, use `{{{{` for a literal brace",
+                                template
+                            )),
+                        }
+                    }
+                    if token != "variable" {
+                        return Err(format!(
+                            "Unknown token `{{{}}}` in placeholder `{}`, expected `{{variable}}`",
+                            token, template
+                        ));
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Variable);
+                }
+                '}' => {
+                    return Err(format!(
+                        "Unmatched `}}` in placeholder `{}`, use `}}}}` for a literal brace",
+                        template
+                    ))
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Placeholder { template, parts })
+    }
+}
+
+impl From<Placeholder> for String {
+    fn from(placeholder: Placeholder) -> Self {
+        placeholder.template
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.template)
+    }
+}
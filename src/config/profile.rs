@@ -0,0 +1,103 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{include, Config, ConfigError, Environment, Specific};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A named set of overrides selected with `--profile` or `EA_PROFILE`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+pub struct Profile {
+    /// Settings merged over the top-level `[environment]` section
+    #[serde(default)]
+    #[schemars(with = "Option<Environment>")]
+    pub environment: Option<toml::Table>,
+    /// Locations added on top of the top-level ones
+    #[serde(default)]
+    pub specific: Specific,
+    /// Ids of locations that are neither applied nor deapplied in this profile
+    #[serde(default)]
+    pub exempt: Vec<String>,
+}
+
+impl Config {
+    /// Applies the named profile on top of the loaded configuration.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            let available: Vec<&str> = self.profiles.keys().map(|k| k.as_str()).collect();
+            ConfigError::UnknownProfile(format!(
+                "{} (available: {})",
+                name,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ))
+        })?;
+
+        if let Some(overrides) = profile.environment {
+            let mut environment = toml::Table::try_from(&self.environment)
+                .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+            include::merge(&mut environment, overrides);
+            self.environment = toml::Value::Table(environment)
+                .try_into()
+                .map_err(|e| ConfigError::ParseError(format!("Profile {}: {}", name, e)))?;
+        }
+
+        for ((_, target), (_, added)) in self.specific.iter_mut().zip(profile.specific.iter()) {
+            target.locations.extend(added.locations.iter().cloned());
+            if added.placeholder.is_some() {
+                target.placeholder = added.placeholder.clone();
+            }
+        }
+
+        let mut unknown: HashSet<&str> = profile.exempt.iter().map(|id| id.as_str()).collect();
+        for (_, file_config) in self.specific.iter_mut() {
+            for loc in &mut file_config.locations {
+                if let Some(id) = loc
+                    .id
+                    .as_deref()
+                    .filter(|id| profile.exempt.iter().any(|e| e == id))
+                {
+                    unknown.remove(id);
+                    loc.override_settings.exempt_apply = true;
+                    loc.override_settings.exempt_deapply = true;
+                }
+            }
+        }
+        if let Some(id) = unknown.into_iter().next() {
+            return Err(ConfigError::UnknownLocation(format!(
+                "{} (exempted by profile {})",
+                id, name
+            )));
+        }
+
+        self.check_ids()?;
+        self.check_locations()?;
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::Config;
+use schemars::{json_schema, Schema, SchemaGenerator};
+use serde_json::Value;
+
+/// Plural aliases `Location` accepts for its fields, which serde's `alias` hides from the schema.
+const LOCATION_ALIASES: [(&str, &str); 2] = [("files", "file"), ("nodes", "node")];
+
+/// Generates the JSON Schema (draft 2020-12) describing the config file.
+pub fn generate() -> Schema {
+    let mut schema = schemars::schema_for!(Config);
+    schema.insert("title".into(), "Env Applier configuration".into());
+    add_location_aliases(&mut schema);
+    schema
+}
+
+/// Describes each alias of a location field with the same shape as the field itself.
+fn add_location_aliases(schema: &mut Schema) {
+    let Some(properties) = schema
+        .get_mut("$defs")
+        .and_then(|defs| defs.get_mut("Location"))
+        .and_then(|location| location.get_mut("properties"))
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+
+    for (alias, field) in LOCATION_ALIASES {
+        let Some(Value::Object(mut property)) = properties.get(field).cloned() else {
+            continue;
+        };
+        property.insert("description".into(), format!("Alias of `{}`", field).into());
+        properties.insert(alias.into(), property.into());
+    }
+}
+
+/// Schema of fields accepting either a single string or a list of strings.
+pub fn string_or_list(_generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } }
+        ]
+    })
+}
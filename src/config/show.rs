@@ -0,0 +1,238 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::config::{Config, ConfigError, Placeholder, Segment, SourceFile};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+/// Settings outside of locations whose origin `--explain` reports.
+const ENVIRONMENT_KEYS: [&str; 6] =
+    ["prefix", "suffix", "variables", "on_missing", "secret_dirs", "env_files"];
+
+/// Output formats of `config show`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+/// Where each setting came from, keyed by its dotted path, e.g. `environment.prefix`.
+pub type Origins = BTreeMap<String, String>;
+
+impl Config {
+    /// Returns the configuration as commands see it, with every location spelled out in full.
+    ///
+    /// # Notes
+    /// * Include lists and profiles are dropped, as they have already been merged
+    /// * File, variable file, env file and secret directory paths are made absolute
+    /// * `on_missing` and `placeholder` are filled in from the format or `[environment]` where unset
+    pub fn resolved(&self) -> Config {
+        let mut config = self.clone();
+        config.extends.clear();
+        config.include.clear();
+        config.profiles.clear();
+        config.environment.env_files = absolute(&self.environment.env_files);
+        config.environment.secret_dirs = self.environment.secret_dirs.as_deref().map(absolute);
+
+        let environment = &self.environment;
+        for (_, file_config) in config.specific.iter_mut() {
+            let placeholder = file_config
+                .placeholder
+                .clone()
+                .unwrap_or_else(|| Placeholder::affixed(&environment.prefix, &environment.suffix));
+
+            for loc in &mut file_config.locations {
+                loc.file = absolute(&loc.file);
+                loc.variable_file = loc.variable_file.as_deref().map(absolute_path);
+                loc.on_missing.get_or_insert(environment.on_missing);
+                // A `default` is written back as-is, and so are value templates unless the location
+                // sets its own placeholder
+                if loc.value.is_none() && loc.placeholder.is_none() && loc.default.is_none() {
+                    loc.placeholder = Some(placeholder.clone());
+                }
+            }
+        }
+
+        config
+    }
+
+    /// Finds the file, line and column every setting and location was defined at.
+    ///
+    /// Settings which no file sets are reported as `default`.
+    pub async fn origins(&self) -> Result<Origins, ConfigError> {
+        let mut keys: Vec<Vec<String>> = ENVIRONMENT_KEYS
+            .iter()
+            .map(|key| vec!["environment".to_string(), key.to_string()])
+            .collect();
+        for (file_format, _) in self.specific.iter() {
+            keys.push(vec![
+                "specific".to_string(),
+                file_format.to_string(),
+                "placeholder".to_string(),
+            ]);
+        }
+
+        let mut origins = Origins::new();
+        for key in &keys {
+            origins.insert(key.join("."), "default".to_string());
+        }
+
+        // Files are visited in merge order so the last one setting a key wins, like it does when merging
+        let mut sources = Vec::new();
+        for path in self.include_tree.files() {
+            let content = tokio::fs::read_to_string(path).await?;
+            sources.push(
+                SourceFile::parse(path, content)
+                    .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))?,
+            );
+        }
+        let mut prefixes = vec![Vec::new()];
+        if let Some(profile) = &self.active_profile {
+            prefixes.push(vec!["profiles".to_string(), profile.clone()]);
+        }
+        for prefix in &prefixes {
+            for source in &sources {
+                for key in &keys {
+                    let segments: Vec<Segment> = prefix
+                        .iter()
+                        .chain(key)
+                        .map(|k| Segment::Key(k.clone()))
+                        .collect();
+                    if let Some(origin) = source.origin(&segments) {
+                        origins.insert(key.join("."), origin.to_string());
+                    }
+                }
+            }
+        }
+
+        for (file_format, file_config) in self.specific.iter() {
+            for (i, loc) in file_config.locations.iter().enumerate() {
+                if let Some(origin) = &loc.origin {
+                    origins.insert(
+                        format!("specific.{}.locations.{}", file_format, i),
+                        origin.to_string(),
+                    );
+                }
+            }
+        }
+
+        Ok(origins)
+    }
+
+    /// Renders the configuration, annotated with `origins` when given.
+    ///
+    /// TOML output carries the origins as comments, YAML and JSON output nest the configuration
+    /// under `config` next to an `origins` map.
+    pub fn render(&self, format: Format, origins: Option<&Origins>) -> Result<String, ConfigError> {
+        let serialize_error =
+            |e: &dyn std::fmt::Display| ConfigError::SerializeError(e.to_string());
+
+        match (format, origins) {
+            (Format::Toml, None) => toml::to_string_pretty(self).map_err(|e| serialize_error(&e)),
+            (Format::Toml, Some(origins)) => {
+                let mut document: DocumentMut = toml::to_string_pretty(self)
+                    .map_err(|e| serialize_error(&e))?
+                    .parse()
+                    .map_err(|e| serialize_error(&e))?;
+                annotate(&mut document, origins);
+                Ok(document.to_string())
+            }
+            (Format::Yaml, None) => {
+                serde_yaml_ng::to_string(self).map_err(|e| serialize_error(&e))
+            }
+            (Format::Yaml, Some(origins)) => serde_yaml_ng::to_string(&Explained {
+                config: self,
+                origins,
+            })
+            .map_err(|e| serialize_error(&e)),
+            (Format::Json, None) => serde_json::to_string_pretty(self)
+                .map(|json| json + "\n")
+                .map_err(|e| serialize_error(&e)),
+            (Format::Json, Some(origins)) => serde_json::to_string_pretty(&Explained {
+                config: self,
+                origins,
+            })
+            .map(|json| json + "\n")
+            .map_err(|e| serialize_error(&e)),
+        }
+    }
+}
+
+/// The configuration next to the origins of its settings.
+#[derive(Serialize)]
+struct Explained<'a> {
+    config: &'a Config,
+    origins: &'a Origins,
+}
+
+/// Adds the origin of every setting as a trailing comment, and of every location or table setting
+/// above its header.
+fn annotate(document: &mut DocumentMut, origins: &Origins) {
+    for (key, origin) in origins {
+        let parts: Vec<&str> = key.split('.').collect();
+        let Some((last, parents)) = parts.split_last() else {
+            continue;
+        };
+        let Some(parent) = parents
+            .iter()
+            .try_fold(document.as_item_mut(), |item, key| item.get_mut(*key))
+        else {
+            continue;
+        };
+
+        if let Ok(index) = last.parse::<usize>() {
+            if let Some(table) = parent
+                .as_array_of_tables_mut()
+                .and_then(|t| t.get_mut(index))
+            {
+                table.decor_mut().set_prefix(format!("\n# {}\n", origin));
+            }
+        } else {
+            match parent.get_mut(*last) {
+                Some(Item::Value(value)) => value.decor_mut().set_suffix(format!(" # {}", origin)),
+                Some(Item::Table(table)) => {
+                    table.decor_mut().set_prefix(format!("\n# {}\n", origin))
+                }
+                // The whole array comes from one file, so its first header carries the origin
+                Some(Item::ArrayOfTables(tables)) => {
+                    if let Some(table) = tables.get_mut(0) {
+                        table.decor_mut().set_prefix(format!("\n# {}\n", origin));
+                    }
+                }
+                Some(Item::None) | None => {}
+            }
+        }
+    }
+}
+
+fn absolute(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths.iter().map(|path| absolute_path(path)).collect()
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,164 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+/// Where in a config file something was defined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Origin {
+    #[serde(rename = "file")]
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// One step of a path into a TOML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A config file parsed with the position of every key kept.
+pub struct SourceFile {
+    path: PathBuf,
+    document: ImDocument<String>,
+}
+
+impl SourceFile {
+    pub fn parse(path: &Path, content: String) -> Result<Self, toml_edit::TomlError> {
+        Ok(SourceFile {
+            path: path.to_path_buf(),
+            document: ImDocument::parse(content)?,
+        })
+    }
+
+    /// Returns the top-level item with the given key.
+    pub fn item(&self, key: &str) -> Option<&Item> {
+        self.document.get(key)
+    }
+
+    /// Returns where the key or array entry at the path is defined.
+    pub fn origin(&self, segments: &[Segment]) -> Option<Origin> {
+        let span = item_span(self.document.as_item(), segments)?;
+        Some(self.origin_at(span.start))
+    }
+
+    /// Returns where each entry of every `locations` array is defined, keyed by the path of the
+    /// table holding the array, e.g. `["specific", "yaml"]`.
+    pub fn location_origins(&self) -> BTreeMap<Vec<String>, Vec<Origin>> {
+        let mut origins = BTreeMap::new();
+        self.collect_locations(self.document.as_table(), &mut Vec::new(), &mut origins);
+        origins
+    }
+
+    fn collect_locations(
+        &self,
+        table: &dyn TableLike,
+        path: &mut Vec<String>,
+        origins: &mut BTreeMap<Vec<String>, Vec<Origin>>,
+    ) {
+        for (key, item) in table.iter() {
+            if key == "locations" {
+                let spans: Vec<Option<Range<usize>>> = match item {
+                    Item::ArrayOfTables(tables) => tables.iter().map(|t| t.span()).collect(),
+                    Item::Value(Value::Array(array)) => array.iter().map(|v| v.span()).collect(),
+                    _ => continue,
+                };
+                origins.insert(
+                    path.clone(),
+                    spans
+                        .into_iter()
+                        .map(|span| self.origin_at(span.map_or(0, |s| s.start)))
+                        .collect(),
+                );
+            } else if let Some(child) = item.as_table_like() {
+                path.push(key.to_string());
+                self.collect_locations(child, path, origins);
+                path.pop();
+            }
+        }
+    }
+
+    fn origin_at(&self, offset: usize) -> Origin {
+        let before = &self.document.raw()[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Origin {
+            path: self.path.clone(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+fn item_span(item: &Item, segments: &[Segment]) -> Option<Range<usize>> {
+    match segments.split_first() {
+        None => item.span(),
+        Some((Segment::Key(_), _)) => table_span(item.as_table_like()?, segments),
+        Some((Segment::Index(index), rest)) => match item {
+            Item::ArrayOfTables(tables) => {
+                let table = tables.get(*index)?;
+                match rest.is_empty() {
+                    true => table.span(),
+                    false => table_span(table, rest),
+                }
+            }
+            Item::Value(Value::Array(array)) => value_span(array.get(*index)?, rest),
+            _ => None,
+        },
+    }
+}
+
+fn table_span(table: &dyn TableLike, segments: &[Segment]) -> Option<Range<usize>> {
+    match segments.split_first()? {
+        (Segment::Key(key), []) => table.key(key)?.span(),
+        (Segment::Key(key), rest) => item_span(table.get(key)?, rest),
+        (Segment::Index(_), _) => None,
+    }
+}
+
+fn value_span(value: &Value, segments: &[Segment]) -> Option<Range<usize>> {
+    match (value, segments.first()) {
+        (_, None) => value.span(),
+        (Value::InlineTable(table), Some(Segment::Key(_))) => table_span(table, segments),
+        (Value::Array(array), Some(Segment::Index(index))) => {
+            value_span(array.get(*index)?, &segments[1..])
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use super::source::{is_variable_name, trim_newline};
+use super::EnvSource;
+use crate::config::{CommandFormat, CommandSource};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Errors running command sources, which include the command's stderr but never its stdout.
+#[derive(Debug, Clone, Error)]
+pub enum CommandError {
+    #[error("[sources.cmd.{0}] has an empty `command`")]
+    Empty(String),
+    #[error("[sources.cmd.{0}] has `format = \"json\"` but no `keys` to map its output")]
+    NoKeys(String),
+    #[error("[sources.cmd.{name}] sets `{variable}`, which is not a valid variable name")]
+    InvalidName { name: String, variable: String },
+    #[error("Failed to run `{command}`: {message}")]
+    Spawn { command: String, message: String },
+    #[error("`{command}` timed out after {timeout}s")]
+    Timeout { command: String, timeout: u64 },
+    #[error("`{command}` failed with {status}: {stderr}")]
+    Failed {
+        command: String,
+        status: String,
+        stderr: String,
+    },
+    #[error("`{command}` did not print a JSON object: {message}")]
+    Json { command: String, message: String },
+    #[error("The output of `{command}` has no `{key}`")]
+    MissingKey { command: String, key: String },
+}
+
+/// Outputs of the commands which ran, shared by sources running the same command.
+type Outputs = Mutex<HashMap<Vec<String>, Result<String, CommandError>>>;
+
+/// A command source, whose command runs the first time one of its variables is looked up.
+pub struct CommandEnv {
+    name: String,
+    /// The variable of a text source
+    variable: String,
+    source: CommandSource,
+    outputs: Arc<Outputs>,
+    vars: OnceLock<Result<BTreeMap<String, String>, CommandError>>,
+}
+
+/// Checks the command sources and returns one per source, in the order of their names.
+///
+/// No command runs until one of its variables is looked up, and a command shared by several
+/// sources runs once.
+pub fn sources(sources: &BTreeMap<String, CommandSource>) -> Result<Vec<CommandEnv>, CommandError> {
+    let outputs = Arc::new(Mutex::new(HashMap::new()));
+    let mut envs = Vec::new();
+    for (name, source) in sources {
+        if source.command.is_empty() {
+            return Err(CommandError::Empty(name.clone()));
+        }
+        // The variables of a source must be known up front, so lookups of other names never run it
+        if source.format == CommandFormat::Json && source.keys.is_empty() {
+            return Err(CommandError::NoKeys(name.clone()));
+        }
+        let variable = source.variable.clone().unwrap_or_else(|| name.clone());
+        let names: Vec<&String> = match source.format {
+            CommandFormat::Text => vec![&variable],
+            CommandFormat::Json => source.keys.keys().collect(),
+        };
+        if let Some(variable) = names.iter().find(|n| !is_variable_name(n)) {
+            return Err(CommandError::InvalidName {
+                name: name.clone(),
+                variable: variable.to_string(),
+            });
+        }
+
+        envs.push(CommandEnv {
+            name: name.clone(),
+            variable,
+            source: source.clone(),
+            outputs: outputs.clone(),
+            vars: OnceLock::new(),
+        });
+    }
+    Ok(envs)
+}
+
+impl CommandEnv {
+    /// Whether `key` would come from this source, without running the command.
+    fn provides(&self, key: &str) -> bool {
+        match self.source.format {
+            CommandFormat::Text => key == self.variable,
+            CommandFormat::Json => self.source.keys.contains_key(key),
+        }
+    }
+
+    fn vars(&self) -> &Result<BTreeMap<String, String>, CommandError> {
+        self.vars.get_or_init(|| {
+            let output = self
+                .outputs
+                .lock()
+                .unwrap()
+                .entry(self.source.command.clone())
+                .or_insert_with(|| run(&self.source.command, self.source.timeout))
+                .clone()?;
+            match self.source.format {
+                CommandFormat::Text => Ok(BTreeMap::from([(
+                    self.variable.clone(),
+                    trim_newline(output),
+                )])),
+                CommandFormat::Json => Ok(parse_json(&self.source, &output)?.into_iter().collect()),
+            }
+        })
+    }
+}
+
+impl EnvSource for CommandEnv {
+    fn name(&self) -> String {
+        format!("cmd {} ({})", self.name, self.source.command.join(" "))
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        if !self.provides(key) {
+            return None;
+        }
+        self.vars().as_ref().ok()?.get(key).cloned()
+    }
+
+    /// Lists the mapped variables without running the command.
+    fn keys(&self) -> Vec<String> {
+        match self.source.format {
+            CommandFormat::Text => vec![self.variable.clone()],
+            CommandFormat::Json => self.source.keys.keys().cloned().collect(),
+        }
+    }
+
+    fn error(&self, key: &str) -> Option<String> {
+        if !self.provides(key) {
+            return None;
+        }
+        self.vars().as_ref().err().map(|e| e.to_string())
+    }
+}
+
+/// Reads the mapped keys of a JSON object. Non-string values are kept as JSON.
+fn parse_json(source: &CommandSource, output: &str) -> Result<Vec<(String, String)>, CommandError> {
+    let command = source.command.join(" ");
+    let json: Value = serde_json::from_str(output).map_err(|e| CommandError::Json {
+        command: command.clone(),
+        message: e.to_string(),
+    })?;
+    let Value::Object(object) = &json else {
+        return Err(CommandError::Json {
+            command,
+            message: "not an object".into(),
+        });
+    };
+
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    source
+        .keys
+        .iter()
+        .map(|(variable, key)| {
+            let value = match key.starts_with('/') {
+                true => json.pointer(key),
+                false => object.get(key),
+            };
+            value
+                .map(|value| (variable.clone(), text(value)))
+                .ok_or_else(|| CommandError::MissingKey {
+                    command: command.clone(),
+                    key: key.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Runs a command and returns its standard output, killing it after `timeout` seconds.
+fn run(argv: &[String], timeout: u64) -> Result<String, CommandError> {
+    let command = argv.join(" ");
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| CommandError::Spawn {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+
+    // Drain both pipes while waiting so a chatty command can't block on a full pipe. The output is
+    // received with the same deadline, as a process the command left behind may keep a pipe open.
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let (stdout_tx, stdout_rx) = mpsc::channel();
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout_tx.send(stdout.read_to_end(&mut buf).map(|_| buf));
+    });
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        let _ = stderr_tx.send(buf);
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    let status = loop {
+        let status = child.try_wait().map_err(|e| CommandError::Spawn {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+        if let Some(status) = status {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CommandError::Timeout { command, timeout });
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+    if !status.success() {
+        let stderr = stderr_rx.recv_timeout(remaining()).unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+        return Err(CommandError::Failed {
+            command,
+            status: status.to_string(),
+            stderr: if stderr.is_empty() {
+                "no output on stderr".into()
+            } else {
+                stderr
+            },
+        });
+    }
+    let stdout = stdout_rx
+        .recv_timeout(remaining())
+        .map_err(|_| CommandError::Timeout {
+            command: command.clone(),
+            timeout,
+        })?
+        .map_err(|e| CommandError::Spawn {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+    String::from_utf8(stdout).map_err(|_| CommandError::Spawn {
+        command,
+        message: "stdout is not valid UTF-8".into(),
+    })
+}
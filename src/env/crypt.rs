@@ -0,0 +1,125 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Marks the contents of an encrypted env file, followed by the base64 nonce and ciphertext.
+const HEADER: &str = "ea-encrypted:v1:";
+const NONCE_LEN: usize = 12;
+/// Key file used when neither `EA_KEY` nor `EA_KEY_FILE` is set
+pub const DEFAULT_KEY_FILE: &str = ".env.key";
+
+/// Errors of encrypted env files, which never include any plaintext.
+#[derive(Debug, Error)]
+pub enum CryptError {
+    #[error("No key found, set EA_KEY or create {0} with `ea env keygen`")]
+    MissingKey(String),
+    #[error("Invalid key in {0}, expected 32 base64-encoded bytes")]
+    InvalidKey(String),
+    #[error("Not an encrypted env file")]
+    NotEncrypted,
+    #[error("Cannot decrypt, the key is wrong or the file was modified")]
+    Decrypt,
+}
+
+/// An AES-256-GCM key used to encrypt env files.
+pub struct Key(Aes256Gcm);
+
+impl Key {
+    /// Generates a random key and returns it base64 encoded, as stored in key files.
+    pub fn generate() -> String {
+        STANDARD.encode(Aes256Gcm::generate_key(OsRng))
+    }
+
+    /// Parses a base64 encoded key, `source` names where it came from in errors.
+    pub fn parse(encoded: &str, source: &str) -> Result<Key, CryptError> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|_| CryptError::InvalidKey(source.to_string()))?;
+        Aes256Gcm::new_from_slice(&bytes)
+            .map(Key)
+            .map_err(|_| CryptError::InvalidKey(source.to_string()))
+    }
+
+    /// Loads the key from `EA_KEY`, or from the file at `EA_KEY_FILE` or `.env.key`, reading
+    /// both variables through `lookup`.
+    pub fn load(lookup: impl Fn(&str) -> Option<String>) -> Result<Key, CryptError> {
+        if let Some(encoded) = lookup("EA_KEY") {
+            return Key::parse(&encoded, "EA_KEY");
+        }
+
+        let path = lookup("EA_KEY_FILE")
+            .map_or_else(|| PathBuf::from(DEFAULT_KEY_FILE), PathBuf::from);
+        match fs::read_to_string(&path) {
+            Ok(encoded) => Key::parse(&encoded, &path.display().to_string()),
+            Err(_) => Err(CryptError::MissingKey(path.display().to_string())),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("encrypting in memory cannot fail");
+
+        let mut payload = nonce.to_vec();
+        payload.extend(ciphertext);
+        format!("{}{}\n", HEADER, STANDARD.encode(payload))
+    }
+
+    pub fn decrypt(&self, content: &str) -> Result<String, CryptError> {
+        let payload = content
+            .trim()
+            .strip_prefix(HEADER)
+            .and_then(|encoded| STANDARD.decode(encoded).ok())
+            .filter(|payload| payload.len() > NONCE_LEN)
+            .ok_or(CryptError::NotEncrypted)?;
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| CryptError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| CryptError::Decrypt)
+    }
+}
+
+/// The key file read when `EA_KEY` is not set.
+pub fn key_file() -> PathBuf {
+    std::env::var_os("EA_KEY_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_KEY_FILE))
+}
+
+/// Whether `path` names an encrypted env file.
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "enc")
+}
@@ -0,0 +1,114 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use super::source::{is_variable_name, Resolution};
+use super::{EnvSource, ProcessEnv, Resolver};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Output formats of `env export`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// `export NAME='value'` lines for `eval`
+    Shell,
+    /// `NAME=value` lines for `docker run --env-file`
+    Docker,
+    Json,
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Docker env files can't hold the multi-line value of `{0}`")]
+    MultiLine(String),
+    #[error("Failed to serialize: {0}")]
+    Serialize(String),
+}
+
+/// Resolves every variable defined by a source other than the process environment, and the
+/// `extra` names, e.g. those referenced by the config, which may come from any source.
+///
+/// A `<NAME>_FILE` variable also lists `<NAME>`. The process environment is only included
+/// entirely with `process`.
+pub fn collect(env: &Resolver, extra: &[String], process: bool) -> BTreeMap<String, Resolution> {
+    let mut names: Vec<String> = extra.to_vec();
+    for layer in env.layers() {
+        if !process && layer.name() == ProcessEnv.name() {
+            continue;
+        }
+        for key in layer.keys() {
+            if let Some(name) = key.strip_suffix("_FILE") {
+                names.push(name.to_string());
+            }
+            names.push(key);
+        }
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| env.resolve(&name).map(|resolution| (name, resolution)))
+        .collect()
+}
+
+/// Hides a value but its first two characters, short values entirely.
+pub fn mask(value: &str) -> String {
+    match value.chars().count() {
+        0 => String::new(),
+        1..=7 => "****".to_string(),
+        _ => value.chars().take(2).chain("****".chars()).collect(),
+    }
+}
+
+/// Renders variables for `env export`, skipping names which can't be exported such as
+/// `vault:` references.
+pub fn render(vars: &BTreeMap<String, String>, format: Format) -> Result<String, ExportError> {
+    let vars = vars.iter().filter(|(name, _)| is_variable_name(name));
+
+    let mut out = String::new();
+    match format {
+        Format::Shell => {
+            for (name, value) in vars {
+                out.push_str(&format!(
+                    "export {}='{}'\n",
+                    name,
+                    value.replace('\'', r"'\''")
+                ));
+            }
+        }
+        Format::Docker => {
+            for (name, value) in vars {
+                if value.contains(['\n', '\r']) {
+                    return Err(ExportError::MultiLine(name.clone()));
+                }
+                out.push_str(&format!("{}={}\n", name, value));
+            }
+        }
+        Format::Json => {
+            let map: BTreeMap<_, _> = vars.collect();
+            out = serde_json::to_string_pretty(&map)
+                .map_err(|e| ExportError::Serialize(e.to_string()))?;
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
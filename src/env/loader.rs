@@ -22,18 +22,22 @@
  * SOFTWARE.
  */
 
-use crate::env::parser;
-use anyhow::{Context, Result};
-use std::collections::HashMap;
+use crate::env::parser::{self, Entry};
+use crate::env::crypt::{self, Key};
+use crate::env::VarMap;
+use anyhow::{anyhow, Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub fn get_env_file_paths(base_path: &Path, mode: &str) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
+    // Encrypted siblings are loaded right after, and take precedence over, the plain files
     let mut add_if_exists = |filename: &str| {
-        let path = base_path.join(filename);
-        if path.exists() {
-            files.push(path);
+        for name in [filename.to_string(), format!("{}.enc", filename)] {
+            let path = base_path.join(name);
+            if path.exists() {
+                files.push(path);
+            }
         }
     };
 
@@ -50,16 +54,70 @@ pub fn get_env_file_paths(base_path: &Path, mode: &str) -> Result<Vec<PathBuf>>
     Ok(files)
 }
 
-pub fn load_and_parse_files(files: &[PathBuf]) -> Result<HashMap<String, String>> {
-    let mut all_vars = HashMap::new();
-
-    for path in files {
-        let content = fs::read_to_string(path)
+/// Loads `files`, returning one layer per file in the same order.
+///
+/// Files ending in `.enc` are decrypted with the key `Key::load` finds through `lookup`.
+///
+/// Variables `lookup` resolves are skipped, as they take precedence over every env file. A
+/// variable defined in several files only appears in the layer of the last one. Values are
+/// expanded once every file is read.
+pub fn load_files(
+    files: &[PathBuf],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<VarMap>> {
+    let mut entries: Vec<(Entry, usize)> = Vec::new();
+    let mut key = None;
+    for (index, path) in files.iter().enumerate() {
+        let mut content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        if crypt::is_encrypted(path) {
+            let decrypted = match &key {
+                Some(key) => Ok(key),
+                None => Key::load(&lookup).map(|loaded| &*key.insert(loaded)),
+            }
+            .and_then(|key| key.decrypt(&content));
+            content = decrypted.map_err(|e| anyhow!("Failed to decrypt {}: {}", path.display(), e))?;
+        }
+
+        let parsed = parser::parse_entries(&content).map_err(|e| {
+            anyhow!("{}:{}: {}", path.display(), e.line, error_message(path, e.message))
+        })?;
+        for entry in parsed {
+            if lookup(&entry.key).is_some() {
+                continue;
+            }
 
-        parser::parse_contents(&content, &mut all_vars)
-            .with_context(|| format!("Failed to parse file: {}", path.display()))?;
+            entries.retain(|(e, _)| e.key != entry.key);
+            entries.push((entry, index));
+        }
     }
 
-    Ok(all_vars)
+    let (entries, sources): (Vec<Entry>, Vec<usize>) = entries.into_iter().unzip();
+    let mut resolved = parser::resolve(&entries, lookup).map_err(|e| {
+        let index = entries.iter().position(|entry| entry.key == e.key).unwrap();
+        let path = &files[sources[index]];
+        let message = error_message(path, e.message);
+        anyhow!("{}:{}: {}", path.display(), entries[index].line, message)
+    })?;
+
+    let layers = files.iter().enumerate().map(|(index, path)| {
+        let vars = entries
+            .iter()
+            .zip(&sources)
+            .filter(|(_, source)| **source == index)
+            .filter_map(|(entry, _)| resolved.remove_entry(&entry.key));
+        VarMap::new(path.display().to_string(), vars.collect::<Vec<_>>())
+    });
+    Ok(layers.collect())
+}
+
+/// Replaces the errors about the content of an encrypted file, which may quote its plaintext.
+pub const ENCRYPTED_ENTRY_ERROR: &str = "Invalid entry in encrypted file";
+
+/// Errors may quote the offending line, which must not leak the plaintext of an encrypted file.
+fn error_message(path: &Path, message: String) -> String {
+    match crypt::is_encrypted(path) {
+        true => ENCRYPTED_ENTRY_ERROR.to_string(),
+        false => message,
+    }
 }
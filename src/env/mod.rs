@@ -22,32 +22,117 @@
  * SOFTWARE.
  */
 
+pub mod command;
+pub mod crypt;
+pub mod export;
 pub mod loader;
 pub mod parser;
+mod source;
+pub mod vault;
 
-use std::collections::HashMap;
+pub use source::{read_secret, EnvSource, ProcessEnv, Resolution, Resolver, SecretDir, VarMap};
+
+use crate::config::{CommandSource, VaultSource};
+use vault::{VaultEnv, VaultError};
+use std::collections::BTreeMap;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Where environment variables are loaded from.
+///
+/// From highest to lowest precedence:
+/// * `overrides`
+/// * The process environment
+/// * `secret_dirs`, earlier directories first
+/// * `vault`
+/// * `commands`, in the order of their names
+/// * `env_files`, later files first
+/// * `config_files`, later files first
+/// * `.env.<mode>.local`, `.env.<mode>`, `.env.local` and `.env` in the working directory
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Selects the `.env.<mode>` files, falls back to `EA_MODE`, `BUN_ENV`, `NODE_ENV` then `development`
+    pub mode: Option<String>,
+    /// Skips the `.env` files in the working directory
+    pub no_dotenv: bool,
+    /// Files from `[environment] env_files`
+    pub config_files: Vec<PathBuf>,
+    /// Files passed with `--env-file`
+    pub env_files: Vec<PathBuf>,
+    /// Variables passed with `--set`
+    pub overrides: Vec<(String, String)>,
+    /// Directories of secret files named after their variable
+    pub secret_dirs: Vec<PathBuf>,
+    /// Vault secret from `[sources.vault]`
+    pub vault: Option<VaultSource>,
+    /// `vault:<path>#<key>` variables used by locations
+    pub vault_references: Vec<String>,
+    /// Command sources from `[sources.cmd]`
+    pub commands: BTreeMap<String, CommandSource>,
+}
+
+/// Loads Vault, the commands and the env files below the overrides, the process environment and the secret directories.
+///
+/// The process environment is only read, never modified.
+pub fn load(options: &Options) -> Result<Resolver, anyhow::Error> {
+    let mut env = Resolver::new(vec![
+        Box::new(VarMap::new("--set", options.overrides.iter().cloned())),
+        Box::new(ProcessEnv),
+    ]);
+    for dir in &options.secret_dirs {
+        env.push(Box::new(SecretDir::new(dir)));
+    }
 
-pub fn load() -> Result<(), anyhow::Error> {
-    let mode = get_current_mode();
-    let files = loader::get_env_file_paths(Path::new("."), &mode)?;
-    let variables = loader::load_and_parse_files(&files)?;
+    let mut files = Vec::new();
+    if !options.no_dotenv {
+        let mode = options.mode.clone().unwrap_or_else(get_current_mode);
+        files = loader::get_env_file_paths(Path::new("."), &mode)?;
+    }
+    files.extend(options.config_files.iter().cloned());
+    files.extend(options.env_files.iter().cloned());
+    let file_layers = loader::load_files(&files, |name| env.get(name))?;
 
-    set_environment_variables(variables);
-    Ok(())
+    // Vault credentials may come from any env file, but the secrets take precedence over them
+    match &options.vault {
+        Some(source) => {
+            let vault = VaultEnv::new(source, |name| {
+                env.get(name)
+                    .or_else(|| file_layers.iter().rev().find_map(|layer| layer.get(name)))
+            });
+            env.push(Box::new(vault));
+        }
+        None => {
+            if let Some(reference) = options.vault_references.first() {
+                return Err(VaultError::NotConfigured(reference.clone()).into());
+            }
+        }
+    }
+    for layer in command::sources(&options.commands)? {
+        env.push(Box::new(layer));
+    }
+    for layer in file_layers.into_iter().rev() {
+        env.push(Box::new(layer));
+    }
+    Ok(env)
+}
+
+/// Parses a `KEY=VALUE` pair passed with `--set`.
+pub fn parse_override(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{}`", arg))?;
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid variable name `{}`", key));
+    }
+
+    Ok((key.to_string(), value.to_string()))
 }
 
 fn get_current_mode() -> String {
-    env::var("BUN_ENV")
+    env::var("EA_MODE")
+        .or_else(|_| env::var("BUN_ENV"))
         .or_else(|_| env::var("NODE_ENV"))
         .unwrap_or_else(|_| "development".into())
 }
-
-fn set_environment_variables(vars: HashMap<String, String>) {
-    for (key, value) in vars {
-        if env::var(&key).is_err() {
-            env::set_var(key, value);
-        }
-    }
-}
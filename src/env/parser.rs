@@ -22,98 +22,569 @@
  * SOFTWARE.
  */
 
-use anyhow::{anyhow, Result};
-use regex::Regex;
 use std::collections::HashMap;
+use thiserror::Error;
 
-pub fn parse_contents(contents: &str, vars: &mut HashMap<String, String>) -> Result<()> {
-    let line_re = Regex::new(r"(?m)^\s*([A-Za-z_][A-Za-z0-9_]*)\s*=\s*(.*?)(\s*#.*)?$")?;
-    // Handle both ${VAR} and $VAR syntax
-    let expand_re = Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))")?;
+/// The operators of `${VAR<operator>word}`, longest first.
+const OPERATORS: [&str; 6] = [":-", ":+", ":?", "-", "+", "?"];
 
-    // First pass: collect raw variables
-    let mut raw_vars = Vec::new();
-    for line in contents.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+/// A malformed line in an env file.
+#[derive(Debug, Error, PartialEq)]
+#[error("line {line}: {message}")]
+pub struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// A `${VAR:?message}` reference to a variable which is not set.
+#[derive(Debug, Error, PartialEq)]
+#[error("{name}: {message}")]
+pub struct ExpandError {
+    pub name: String,
+    pub message: String,
+}
+
+/// A variable whose value cannot be expanded.
+#[derive(Debug, Error, PartialEq)]
+#[error("{message}")]
+pub struct ResolveError {
+    pub key: String,
+    pub message: String,
+}
+
+/// A `KEY=value` assignment read from an env file.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    /// The line the assignment starts on
+    pub line: usize,
+    /// Whether `$VAR` references are expanded, single-quoted values are taken literally
+    pub expand: bool,
+}
+
+/// Expands the values of `entries`, later entries replacing earlier ones with the same key.
+///
+/// Values are expanded in dependency order so a variable may reference one defined after it.
+/// References to variables which aren't among `entries`, or to the variable itself, are
+/// resolved through `lookup`.
+pub fn resolve(
+    entries: &[Entry],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<HashMap<String, String>, ResolveError> {
+    let mut expander = Expander {
+        entries: entries.iter().map(|e| (e.key.as_str(), e)).collect(),
+        lookup,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+    };
+    for entry in entries {
+        expander.resolve(&entry.key)?;
+    }
+
+    Ok(expander.resolved)
+}
+
+struct Expander<'a, F> {
+    entries: HashMap<&'a str, &'a Entry>,
+    lookup: F,
+    resolved: HashMap<String, String>,
+    /// The keys being resolved, used to report cycles
+    stack: Vec<&'a str>,
+}
+
+impl<'a, F: Fn(&str) -> Option<String>> Expander<'a, F> {
+    fn resolve(&mut self, key: &'a str) -> Result<(), ResolveError> {
+        if self.resolved.contains_key(key) {
+            return Ok(());
         }
 
-        let caps = line_re
-            .captures(line)
-            .ok_or_else(|| anyhow!("Invalid line format: {}", line))?;
+        let entry = self.entries[key];
+        if !entry.expand {
+            self.resolved.insert(key.to_string(), entry.value.clone());
+            return Ok(());
+        }
 
-        let key = caps[1].trim().to_string();
-        let raw_value = caps[2].trim();
+        if let Some(start) = self.stack.iter().position(|k| *k == key) {
+            let mut cycle = self.stack[start..].to_vec();
+            cycle.push(key);
+            return Err(ResolveError {
+                key: key.to_string(),
+                message: format!("Circular reference: {}", cycle.join(" -> ")),
+            });
+        }
 
-        // Skip if already set in environment
-        if std::env::var(&key).is_ok() {
-            continue;
+        // A variable referencing itself, as in `PATH=$PATH:/bin`, reads the value from `lookup`
+        self.stack.push(key);
+        for name in references(&entry.value) {
+            if name == key {
+                continue;
+            }
+            if let Some((dependency, _)) = self.entries.get_key_value(name.as_str()) {
+                self.resolve(dependency)?;
+            }
+        }
+        self.stack.pop();
+
+        let value = expand(&entry.value, |name| {
+            self.resolved
+                .get(name)
+                .filter(|_| name != key)
+                .cloned()
+                .or_else(|| (self.lookup)(name))
+        })
+        .map_err(|e| ResolveError {
+            key: key.to_string(),
+            message: e.to_string(),
+        })?;
+        self.resolved.insert(key.to_string(), value);
+
+        Ok(())
+    }
+}
+
+/// Parses the assignments of an env file in order of appearance.
+///
+/// # Notes
+/// * Assignments may be prefixed with `export`
+/// * Double-quoted values support `\n`, `\r`, `\t`, `\"` and `\\` escapes
+/// * Single-quoted values are taken literally and never expanded
+/// * Quoted values may span several lines
+/// * A `#` starts a comment only at the start of a line or after whitespace outside of quotes
+pub fn parse_entries(contents: &str) -> Result<Vec<Entry>, SyntaxError> {
+    let mut scanner = Scanner {
+        chars: contents.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut entries = Vec::new();
+
+    loop {
+        scanner.skip_while(char::is_whitespace);
+        match scanner.peek() {
+            None => break,
+            Some('#') => {
+                scanner.skip_while(|c| c != '\n');
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let line = scanner.line;
+        let mut key = scanner.take_while(is_name_char);
+        if key == "export" && scanner.peek().is_some_and(is_blank) {
+            scanner.skip_while(is_blank);
+            key = scanner.take_while(is_name_char);
+        }
+        if !is_valid_name(&key) {
+            let token = key + &scanner.take_while(|c| !c.is_whitespace() && c != '=');
+            return Err(scanner.error(format!("Invalid variable name `{}`", token)));
+        }
+
+        scanner.skip_while(is_blank);
+        if scanner.peek() != Some('=') {
+            return Err(scanner.error(format!("Expected `=` after `{}`", key)));
+        }
+        scanner.next();
+        let spaced = !scanner.take_while(is_blank).is_empty();
+
+        let (value, expand) = match scanner.peek() {
+            Some('"') => (scanner.double_quoted(&key)?, true),
+            Some('\'') => (scanner.single_quoted(&key)?, false),
+            _ => (scanner.unquoted(spaced), true),
+        };
+        scanner.end_of_line()?;
+
+        entries.push(Entry {
+            key,
+            value,
+            line,
+            expand,
+        });
+    }
+
+    Ok(entries)
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek().filter(|c| predicate(*c)) {
+            taken.push(c);
+            self.next();
+        }
+        taken
+    }
+
+    fn skip_while(&mut self, predicate: impl Fn(char) -> bool) {
+        self.take_while(predicate);
+    }
+
+    fn error(&self, message: String) -> SyntaxError {
+        SyntaxError {
+            line: self.line,
+            message,
+        }
+    }
+
+    fn double_quoted(&mut self, key: &str) -> Result<String, SyntaxError> {
+        let line = self.line;
+        self.next();
+
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => break,
+                },
+                Some(c) => value.push(c),
+                None => break,
+            }
         }
 
-        raw_vars.push((key, raw_value.to_string()));
+        Err(SyntaxError {
+            line,
+            message: format!("Unterminated double-quoted value of `{}`", key),
+        })
+    }
+
+    fn single_quoted(&mut self, key: &str) -> Result<String, SyntaxError> {
+        let line = self.line;
+        self.next();
+
+        let value = self.take_while(|c| c != '\'');
+        match self.next() {
+            Some(_) => Ok(value),
+            None => Err(SyntaxError {
+                line,
+                message: format!("Unterminated single-quoted value of `{}`", key),
+            }),
+        }
     }
 
-    // Insert all variables with unquoted values first
-    for (key, raw_value) in &raw_vars {
-        vars.insert(key.clone(), unquote_value(raw_value));
+    /// Reads up to the end of the line, skipping a trailing comment. `spaced` tells whether blanks preceded the value.
+    fn unquoted(&mut self, spaced: bool) -> String {
+        let mut value = String::new();
+        while let Some(c) = self.peek().filter(|c| *c != '\n') {
+            let after_blank = match value.is_empty() {
+                true => spaced,
+                false => value.ends_with(is_blank),
+            };
+            if c == '#' && after_blank {
+                self.skip_while(|c| c != '\n');
+                break;
+            }
+            value.push(c);
+            self.next();
+        }
+        value.trim_end().to_string()
     }
 
-    // Multi-pass expansion until no more changes
-    let mut iterations = 0;
-    let max_iterations = 15; // Prevent circular referencing forever
-    let mut changed = true;
+    /// Skips trailing whitespace and comments, failing on anything else before the end of the line.
+    fn end_of_line(&mut self) -> Result<(), SyntaxError> {
+        let blank = self.take_while(is_blank);
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some('#') if !blank.is_empty() => {
+                self.skip_while(|c| c != '\n');
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Unexpected `{}` after quoted value", c))),
+        }
+    }
+}
 
-    while changed && iterations < max_iterations {
-        changed = false;
-        iterations += 1;
+fn is_blank(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
 
-        // Expand variables with support for both syntaxes
-        for (key, value) in vars.clone() {
-            let expanded = expand_re
-                .replace_all(&value, |caps: &regex::Captures| {
-                    let var_name = caps
-                        .get(1)
-                        .or_else(|| caps.get(2))
-                        .map(|m| m.as_str())
-                        .unwrap_or("");
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// Expands `$VAR` and `${VAR}` references using `lookup`, along with the shell operators:
+///
+/// * `${VAR:-word}` / `${VAR-word}` use `word` when the variable is unset or empty / unset
+/// * `${VAR:+word}` / `${VAR+word}` use `word` when the variable is set and not empty / set
+/// * `${VAR:?message}` / `${VAR?message}` fail with `message` when the variable is unset or empty / unset
+/// * `$$` is a literal `$`
+///
+/// Like in a shell, `word` may itself contain references, which are only expanded when it's
+/// used. Other unknown variables expand to an empty string.
+pub fn expand(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ExpandError> {
+    expand_tokens(value, true, &lookup)
+}
+
+/// Like `expand`, but only recognizes `${...}` references and takes any other `$` literally,
+/// for paths such as a `$schema` node.
+pub fn expand_braced(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ExpandError> {
+    expand_tokens(value, false, &lookup)
+}
+
+fn expand_tokens<F: Fn(&str) -> Option<String>>(
+    value: &str,
+    bare: bool,
+    lookup: &F,
+) -> Result<String, ExpandError> {
+    let mut expanded = String::with_capacity(value.len());
+    for token in tokens(value, bare) {
+        match token {
+            Token::Text(text) => expanded.push_str(text),
+            Token::Escape => expanded.push('$'),
+            Token::Variable {
+                name,
+                operator,
+                word,
+            } => {
+                let word = || expand_tokens(word, bare, lookup);
+                expanded.push_str(&substitute(name, operator, lookup(name), word)?);
+            }
+        }
+    }
+
+    Ok(expanded)
+}
 
-                    vars.get(var_name)
-                        .map(|v| v.clone())
-                        .or_else(|| std::env::var(var_name).ok().map(|s| s))
-                        .unwrap_or(String::new())
-                        .to_owned()
-                })
-                .to_string();
+/// Returns the names of all variables referenced in `value`, including those in the words of
+/// operators, in order of appearance.
+pub fn references(value: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    references_into(value, true, &mut names);
+    names
+}
 
-            if expanded != value {
-                vars.insert(key.clone(), expanded);
-                changed = true;
+fn references_into(value: &str, bare: bool, names: &mut Vec<String>) {
+    for token in tokens(value, bare) {
+        if let Token::Variable { name, word, .. } = token {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
             }
+            references_into(word, bare, names);
         }
     }
+}
+
+/// Returns the variables referenced in `value` which `lookup` cannot resolve and which have no
+/// fallback, including `:?` references to empty variables and references in a fallback which
+/// is used.
+pub fn unresolved(value: &str, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let mut names = Vec::new();
+    unresolved_into(value, true, &lookup, &mut names);
+    names
+}
+
+/// Like `unresolved`, but only for `${...}` references, see `expand_braced`.
+pub fn unresolved_braced(value: &str, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let mut names = Vec::new();
+    unresolved_into(value, false, &lookup, &mut names);
+    names
+}
+
+fn unresolved_into<F: Fn(&str) -> Option<String>>(
+    value: &str,
+    bare: bool,
+    lookup: &F,
+    names: &mut Vec<String>,
+) {
+    for token in tokens(value, bare) {
+        let Token::Variable {
+            name,
+            operator,
+            word,
+        } = token
+        else {
+            continue;
+        };
 
-    if iterations >= max_iterations {
-        println!("Possible circular reference detected in environment variables!");
+        let value = lookup(name);
+        let set = value.is_some();
+        let non_empty = value.is_some_and(|v| !v.is_empty());
+        let (missing, word_used) = match operator {
+            None | Some("?") => (!set, false),
+            Some(":?") => (!non_empty, false),
+            Some("-") => (false, !set),
+            Some(":-") => (false, !non_empty),
+            Some("+") => (false, set),
+            _ => (false, non_empty),
+        };
+        if missing && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        if word_used {
+            unresolved_into(word, bare, lookup, names);
+        }
     }
+}
 
-    Ok(())
+/// A piece of a value to expand.
+enum Token<'a> {
+    Text(&'a str),
+    /// `$$`, a literal `$`
+    Escape,
+    Variable {
+        name: &'a str,
+        operator: Option<&'a str>,
+        word: &'a str,
+    },
 }
 
-fn unquote_value(value: &str) -> String {
-    let value = value.trim();
-    if value.len() < 2 {
-        return value.to_string();
+/// Splits `value` into text and references. Without `bare`, only `${...}` references are
+/// recognized and any other `$` is literal.
+///
+/// The word of an operator ends at the `}` balancing the opening one, so it may contain
+/// references itself. A `$` which doesn't start a complete reference is kept as text.
+fn tokens(value: &str, bare: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut text = 0;
+    let mut pos = 0;
+    while let Some(offset) = value[pos..].find('$') {
+        let start = pos + offset;
+        let Some((token, end)) = reference(value, start, bare) else {
+            pos = start + 1;
+            continue;
+        };
+        if text < start {
+            tokens.push(Token::Text(&value[text..start]));
+        }
+        tokens.push(token);
+        pos = end;
+        text = end;
+    }
+    if text < value.len() {
+        tokens.push(Token::Text(&value[text..]));
     }
+    tokens
+}
+
+/// Reads the reference starting at the `$` at `start`, returning it and the index past its end.
+fn reference(value: &str, start: usize, bare: bool) -> Option<(Token<'_>, usize)> {
+    let rest = &value[start + 1..];
+    let Some(braced) = rest.strip_prefix('{') else {
+        if !bare {
+            return None;
+        }
+        if rest.starts_with('$') {
+            return Some((Token::Escape, start + 2));
+        }
+        let name = &rest[..name_length(rest)];
+        let token = Token::Variable {
+            name,
+            operator: None,
+            word: "",
+        };
+        return (!name.is_empty()).then_some((token, start + 1 + name.len()));
+    };
 
-    let quote = value.chars().next().unwrap();
-    if quote != '"' && quote != '\'' {
-        return value.to_string();
+    let name = &braced[..name_length(braced)];
+    if name.is_empty() {
+        return None;
+    }
+    let after = &braced[name.len()..];
+    let operator_start = start + 2 + name.len();
+    if after.starts_with('}') {
+        let token = Token::Variable {
+            name,
+            operator: None,
+            word: "",
+        };
+        return Some((token, operator_start + 1));
     }
 
-    if value.chars().last().unwrap() != quote {
-        return value.to_string();
+    let operator = OPERATORS.into_iter().find(|op| after.starts_with(op))?;
+    let word_start = operator_start + operator.len();
+    let mut depth = 1;
+    for (offset, c) in value[word_start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            let token = Token::Variable {
+                name,
+                operator: Some(operator),
+                word: &value[word_start..word_start + offset],
+            };
+            return Some((token, word_start + offset + 1));
+        }
     }
+    None
+}
+
+/// The length of the variable name `text` starts with, 0 if it doesn't start with one.
+fn name_length(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return 0;
+    }
+    text.find(|c: char| !is_name_char(c)).unwrap_or(text.len())
+}
+
+/// Substitutes a reference, expanding its `word` only when the operator uses it.
+fn substitute(
+    name: &str,
+    operator: Option<&str>,
+    value: Option<String>,
+    word: impl Fn() -> Result<String, ExpandError>,
+) -> Result<String, ExpandError> {
+    let non_empty = value.clone().filter(|v| !v.is_empty());
+    let missing = |default: &str| {
+        let message = word()?;
+        Err(ExpandError {
+            name: name.to_string(),
+            message: match message.is_empty() {
+                true => default.to_string(),
+                false => message,
+            },
+        })
+    };
 
-    value[1..value.len() - 1].to_string()
+    match operator {
+        None => Ok(value.unwrap_or_default()),
+        Some("-") => value.map_or_else(&word, Ok),
+        Some(":-") => non_empty.map_or_else(&word, Ok),
+        Some("+") => value.map_or(Ok(String::new()), |_| word()),
+        Some(":+") => non_empty.map_or(Ok(String::new()), |_| word()),
+        Some("?") => value.map_or_else(|| missing("parameter not set"), Ok),
+        _ => non_empty.map_or_else(|| missing("parameter null or not set"), Ok),
+    }
 }
@@ -0,0 +1,256 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A source of environment variables, such as the process environment or an env file.
+pub trait EnvSource: Send + Sync {
+    /// Describes the source, e.g. `process environment` or the path of an env file
+    fn name(&self) -> String;
+
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// The names of the variables the source defines, sorted
+    fn keys(&self) -> Vec<String>;
+
+    /// Describes why `key` could not be read, for sources which read it on demand and can fail
+    fn error(&self, _key: &str) -> Option<String> {
+        None
+    }
+}
+
+/// The environment of the running process.
+#[derive(Debug)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn name(&self) -> String {
+        "process environment".to_string()
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = std::env::vars_os()
+            .filter_map(|(key, _)| key.into_string().ok())
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// A fixed set of variables, used for `--set` overrides and loaded env files.
+#[derive(Debug, Clone)]
+pub struct VarMap {
+    name: String,
+    vars: BTreeMap<String, String>,
+}
+
+impl VarMap {
+    pub fn new(name: impl Into<String>, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        VarMap {
+            name: name.into(),
+            vars: vars.into_iter().collect(),
+        }
+    }
+}
+
+impl EnvSource for VarMap {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.vars.get(key).cloned()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.vars.keys().cloned().collect()
+    }
+}
+
+/// A directory of secret files, such as Docker's `/run/secrets`, where each file name is a variable name.
+#[derive(Debug, Clone)]
+pub struct SecretDir {
+    path: PathBuf,
+}
+
+impl SecretDir {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        SecretDir { path: path.into() }
+    }
+}
+
+impl EnvSource for SecretDir {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        if !is_variable_name(key) {
+            return None;
+        }
+        read_secret(&self.path.join(key)).ok()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+
+        let mut keys: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| is_variable_name(name))
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// Reads a secret file, dropping a single trailing newline.
+pub fn read_secret(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path).map(trim_newline)
+}
+
+/// Drops a single trailing `\n` or `\r\n`.
+pub(crate) fn trim_newline(mut content: String) -> String {
+    if content.ends_with('\n') {
+        content.pop();
+        if content.ends_with('\r') {
+            content.pop();
+        }
+    }
+    content
+}
+
+pub(crate) fn is_variable_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Where a variable's value was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    pub value: String,
+    /// The name of the source, see `EnvSource::name`
+    pub source: String,
+}
+
+enum Found {
+    Value(String),
+    /// The path a `<KEY>_FILE` variable points to
+    File(String),
+}
+
+/// Looks variables up in a stack of sources, the first source defining a variable wins.
+pub struct Resolver {
+    layers: Vec<Box<dyn EnvSource>>,
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.layers.iter().map(|layer| layer.name()).collect();
+        f.debug_struct("Resolver").field("layers", &names).finish()
+    }
+}
+
+impl Resolver {
+    /// Creates a resolver from sources ordered from highest to lowest precedence.
+    pub fn new(layers: Vec<Box<dyn EnvSource>>) -> Self {
+        Resolver { layers }
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.resolve(key).map(|resolution| resolution.value)
+    }
+
+    /// Finds a variable along with the source it came from.
+    ///
+    /// A source setting `<KEY>_FILE` instead of `<KEY>` provides the contents of that file. The
+    /// variable is unresolved if the file cannot be read, see `secret_error`.
+    pub fn resolve(&self, key: &str) -> Option<Resolution> {
+        match self.find(key)? {
+            (layer, Found::Value(value)) => Some(Resolution {
+                value,
+                source: layer.name(),
+            }),
+            (layer, Found::File(path)) => Some(Resolution {
+                value: read_secret(Path::new(&path)).ok()?,
+                source: format!("{} ({}_FILE={})", layer.name(), key, path),
+            }),
+        }
+    }
+
+    /// Describes why the `<KEY>_FILE` a variable resolves through cannot be read, if it can't.
+    pub fn secret_error(&self, key: &str) -> Option<String> {
+        let (_, Found::File(path)) = self.find(key)? else {
+            return None;
+        };
+        read_secret(Path::new(&path))
+            .err()
+            .map(|e| format!("Cannot read `{}` from `{}_FILE`: {}", path, key, e))
+    }
+
+    /// Describes why a source which would be asked for `key`, such as Vault, failed to read it.
+    pub fn source_error(&self, key: &str) -> Option<String> {
+        for layer in &self.layers {
+            if layer.get(key).is_some() || layer.get(&format!("{}_FILE", key)).is_some() {
+                return None;
+            }
+            if let Some(error) = layer.error(key) {
+                return Some(error);
+            }
+        }
+        None
+    }
+
+    /// Finds the first source defining `key` or `<KEY>_FILE`.
+    fn find(&self, key: &str) -> Option<(&dyn EnvSource, Found)> {
+        self.layers.iter().find_map(|layer| {
+            let found = match layer.get(key) {
+                Some(value) => Found::Value(value),
+                None => Found::File(layer.get(&format!("{}_FILE", key))?),
+            };
+            Some((layer.as_ref(), found))
+        })
+    }
+
+    /// Adds a source below the existing ones.
+    pub fn push(&mut self, layer: Box<dyn EnvSource>) {
+        self.layers.push(layer);
+    }
+
+    /// The sources, from highest to lowest precedence.
+    pub fn layers(&self) -> &[Box<dyn EnvSource>] {
+        &self.layers
+    }
+}
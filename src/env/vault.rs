@@ -0,0 +1,295 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use super::EnvSource;
+use crate::config::VaultSource;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Prefix of location variables read from Vault, as in `vault:db/creds#password`.
+pub const REFERENCE_PREFIX: &str = "vault:";
+
+/// Errors reading secrets from Vault.
+#[derive(Debug, Clone, Error)]
+pub enum VaultError {
+    #[error("No Vault address, set `address` in [sources.vault] or VAULT_ADDR")]
+    MissingAddress,
+    #[error("No Vault credentials, set VAULT_TOKEN or VAULT_ROLE_ID and VAULT_SECRET_ID")]
+    MissingCredentials,
+    #[error("`{0}` is used but [sources.vault] is not configured")]
+    NotConfigured(String),
+    #[error("Invalid Vault reference `{0}`, expected `vault:<path>#<key>`")]
+    InvalidReference(String),
+    #[error("Vault secret `{path}` has no key `{key}`")]
+    MissingKey { path: String, key: String },
+    #[error("Request to {url} failed: {message}")]
+    Request { url: String, message: String },
+    #[error("Vault returned {status} for {url}: {message}")]
+    Status {
+        url: String,
+        status: u16,
+        message: String,
+    },
+    #[error("Unexpected response from {url}: {message}")]
+    Response { url: String, message: String },
+}
+
+/// Splits a `vault:<path>#<key>` reference into its path and key.
+pub fn parse_reference(reference: &str) -> Result<(&str, &str), VaultError> {
+    reference
+        .strip_prefix(REFERENCE_PREFIX)
+        .and_then(|rest| rest.split_once('#'))
+        .filter(|(path, key)| !path.is_empty() && !key.is_empty())
+        .ok_or_else(|| VaultError::InvalidReference(reference.to_string()))
+}
+
+/// Variables the credentials and the address are read from.
+const CREDENTIALS: [&str; 4] = [
+    "VAULT_ADDR",
+    "VAULT_TOKEN",
+    "VAULT_ROLE_ID",
+    "VAULT_SECRET_ID",
+];
+
+/// Secrets read from Vault the first time they're looked up.
+///
+/// The keys of `source.path` are exposed under their own names, each reference under its
+/// full `vault:<path>#<key>` name. Each secret is read at most once, and nothing is sent to
+/// Vault until a variable is looked up.
+pub struct VaultEnv {
+    source: VaultSource,
+    credentials: BTreeMap<&'static str, String>,
+    client: OnceLock<Result<Client, VaultError>>,
+    secrets: Mutex<BTreeMap<String, Result<Secret, VaultError>>>,
+}
+
+type Secret = BTreeMap<String, String>;
+
+impl VaultEnv {
+    /// Reads the credentials and the address through `lookup`, without contacting Vault.
+    pub fn new(source: &VaultSource, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        VaultEnv {
+            source: source.clone(),
+            credentials: CREDENTIALS
+                .into_iter()
+                .filter_map(|name| Some((name, lookup(name)?)))
+                .collect(),
+            client: OnceLock::new(),
+            secrets: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Looks `key` up in the secret it belongs to, if any.
+    fn find(&self, key: &str) -> Option<Result<Option<String>, VaultError>> {
+        let (path, key) = match key.starts_with(REFERENCE_PREFIX) {
+            true => match parse_reference(key) {
+                Ok(reference) => reference,
+                Err(e) => return Some(Err(e)),
+            },
+            false => (self.source.path.as_deref()?, key),
+        };
+        Some(self.secret(path).map(|secret| secret.get(key).cloned()))
+    }
+
+    fn secret(&self, path: &str) -> Result<Secret, VaultError> {
+        let mut secrets = self.secrets.lock().unwrap();
+        secrets
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                let client = self.client.get_or_init(|| {
+                    Client::connect(&self.source, |name| self.credentials.get(name).cloned())
+                });
+                client.as_ref().map_err(Clone::clone)?.read(path)
+            })
+            .clone()
+    }
+}
+
+impl EnvSource for VaultEnv {
+    fn name(&self) -> String {
+        match &self.source.path {
+            Some(path) => format!("vault {}/{}", self.source.mount, path),
+            None => format!("vault {}", self.source.mount),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.find(key)?.ok().flatten()
+    }
+
+    /// Only lists the keys of `source.path`, references aren't known in advance.
+    fn keys(&self) -> Vec<String> {
+        match &self.source.path {
+            Some(path) => self.secret(path).map(|secret| secret.into_keys().collect()),
+            None => Ok(Vec::new()),
+        }
+        .unwrap_or_default()
+    }
+
+    fn error(&self, key: &str) -> Option<String> {
+        match self.find(key)? {
+            Ok(Some(_)) => None,
+            // Names outside of references may well not be in `source.path`
+            Ok(None) if !key.starts_with(REFERENCE_PREFIX) => None,
+            Ok(None) => {
+                let (path, key) = parse_reference(key).ok()?;
+                Some(
+                    VaultError::MissingKey {
+                        path: path.to_string(),
+                        key: key.to_string(),
+                    }
+                    .to_string(),
+                )
+            }
+            Err(e) => Some(e.to_string()),
+        }
+    }
+}
+
+/// An authenticated connection to a Vault server.
+struct Client {
+    agent: ureq::Agent,
+    address: String,
+    mount: String,
+    token: String,
+}
+
+impl Client {
+    /// Resolves the address and logs in through AppRole when no token is set.
+    fn connect(
+        source: &VaultSource,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Client, VaultError> {
+        let address = source
+            .address
+            .clone()
+            .or_else(|| lookup("VAULT_ADDR"))
+            .ok_or(VaultError::MissingAddress)?;
+        let mut client = Client {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(source.timeout))
+                .build(),
+            address: address.trim_end_matches('/').to_string(),
+            mount: source.mount.trim_matches('/').to_string(),
+            token: String::new(),
+        };
+
+        client.token = match lookup("VAULT_TOKEN") {
+            Some(token) => token,
+            None => {
+                let (Some(role_id), Some(secret_id)) =
+                    (lookup("VAULT_ROLE_ID"), lookup("VAULT_SECRET_ID"))
+                else {
+                    return Err(VaultError::MissingCredentials);
+                };
+                client.login(&role_id, &secret_id)?
+            }
+        };
+        Ok(client)
+    }
+
+    fn login(&self, role_id: &str, secret_id: &str) -> Result<String, VaultError> {
+        let url = format!("{}/v1/auth/approle/login", self.address);
+        let body = serde_json::json!({ "role_id": role_id, "secret_id": secret_id });
+        let response = self.agent.post(&url).send_json(body);
+        let json = self.json(&url, response)?;
+        json.pointer("/auth/client_token")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| VaultError::Response {
+                url,
+                message: "missing `auth.client_token`".into(),
+            })
+    }
+
+    /// Reads the latest version of a KV v2 secret, non-string values are kept as JSON.
+    fn read(&self, path: &str) -> Result<Secret, VaultError> {
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.address,
+            self.mount,
+            path.trim_matches('/')
+        );
+        let response = self
+            .agent
+            .get(&url)
+            .set("X-Vault-Token", &self.token)
+            .call();
+        let json = self.json(&url, response)?;
+        let data = json
+            .pointer("/data/data")
+            .and_then(Value::as_object)
+            .ok_or_else(|| VaultError::Response {
+                url,
+                message: "missing `data.data`".into(),
+            })?;
+
+        Ok(data
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+
+    fn json(
+        &self,
+        url: &str,
+        response: Result<ureq::Response, ureq::Error>,
+    ) -> Result<Value, VaultError> {
+        match response {
+            Ok(response) => response.into_json().map_err(|e| VaultError::Response {
+                url: url.to_string(),
+                message: e.to_string(),
+            }),
+            Err(ureq::Error::Status(status, response)) => {
+                // Vault reports failures as `{"errors": [...]}`
+                let errors = response
+                    .into_json::<Value>()
+                    .ok()
+                    .and_then(|json| json.get("errors").cloned())
+                    .and_then(|errors| serde_json::from_value::<Vec<String>>(errors).ok())
+                    .filter(|errors| !errors.is_empty());
+                Err(VaultError::Status {
+                    url: url.to_string(),
+                    status,
+                    message: errors.map_or_else(|| "no details".to_string(), |e| e.join(", ")),
+                })
+            }
+            Err(ureq::Error::Transport(transport)) => Err(VaultError::Request {
+                url: url.to_string(),
+                // The transport's own message repeats the URL
+                message: std::error::Error::source(&transport)
+                    .map_or_else(|| transport.kind().to_string(), |e| e.to_string()),
+            }),
+        }
+    }
+}
@@ -29,8 +29,12 @@ mod env;
 mod tests;
 mod utils;
 
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use env::crypt::{self, Key};
+use env::export;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "Env-Applier-RS")]
@@ -40,6 +44,52 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+#[derive(Args)]
+struct GlobalArgs {
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Profile to apply on top of the config (defaults to EA_PROFILE)"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long = "env-file",
+        global = true,
+        value_name = "FILE",
+        help = "Load variables from this file, later files take precedence"
+    )]
+    env_files: Vec<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "MODE",
+        help = "Load .env.<MODE> files (defaults to EA_MODE, BUN_ENV, NODE_ENV, then development)"
+    )]
+    mode: Option<String>,
+    #[arg(long, global = true, help = "Don't load the .env files in the working directory")]
+    no_dotenv: bool,
+    #[arg(
+        long = "set",
+        global = true,
+        value_name = "KEY=VALUE",
+        value_parser = env::parse_override,
+        help = "Set a variable, overriding every other source"
+    )]
+    overrides: Vec<(String, String)>,
+}
+
+impl GlobalArgs {
+    /// The selected profile, falling back to `EA_PROFILE`.
+    fn profile(&self) -> Option<String> {
+        self.profile.clone().or_else(|| std::env::var("EA_PROFILE").ok())
+    }
 }
 
 #[derive(Subcommand)]
@@ -48,40 +98,270 @@ enum Commands {
     Apply {
         #[arg(short, long, value_name = "FILE", help = "Path to config file")]
         config: Option<PathBuf>,
+        #[command(flatten)]
+        selection: SelectionArgs,
     },
     // Deapply configuration
     Deapply {
         #[arg(short, long, value_name = "FILE", help = "Path to config file")]
         config: Option<PathBuf>,
+        #[command(flatten)]
+        selection: SelectionArgs,
     },
     // Parse configuration
     Parse {
         #[arg(short, long, value_name = "FILE", help = "Path to config file")]
         config: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "FORMAT",
+            value_parser = ["text", "json"],
+            default_value = "text",
+            help = "Print diagnostics as text or as JSON"
+        )]
+        output: String,
     },
     // List files defined in configuration
     Files {
         #[arg(short, long, value_name = "FILE", help = "Path to config file")]
         config: Option<PathBuf>,
+        #[command(flatten)]
+        selection: SelectionArgs,
+    },
+    // Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    // Print the JSON Schema of the configuration file
+    Schema {
+        #[arg(short, long, value_name = "FILE", help = "Write the schema to a file instead of stdout")]
+        output: Option<PathBuf>,
+    },
+    // Manage env files
+    Env {
+        #[command(subcommand)]
+        command: EnvCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    // Print the configuration after includes and the profile are merged
+    Show {
+        #[arg(short, long, value_name = "FILE", help = "Path to config file")]
+        config: Option<PathBuf>,
+        #[arg(long, help = "Print every location's effective settings with absolute paths")]
+        resolved: bool,
+        #[arg(
+            short,
+            long,
+            value_name = "FORMAT",
+            value_parser = ["toml", "yaml", "json"],
+            default_value = "toml",
+            help = "Output format"
+        )]
+        format: String,
+        #[arg(long, help = "Annotate where each setting and location was defined")]
+        explain: bool,
+    },
+    // Rewrite the config and the files it includes to the current format version
+    Migrate {
+        #[arg(short, long, value_name = "FILE", help = "Path to config file")]
+        config: Option<PathBuf>,
+        #[arg(long, help = "Only report files needing a migration, failing if there are any")]
+        check: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum EnvCommands {
+    // Encrypt an env file, writing <FILE>.enc
+    Encrypt {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        #[arg(short, long, value_name = "FILE", help = "Write to this file instead of <FILE>.enc")]
+        output: Option<PathBuf>,
+    },
+    // Decrypt an env file, writing it without the .enc extension
+    Decrypt {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        #[arg(short, long, value_name = "FILE", help = "Write to this file instead of <FILE> without .enc")]
+        output: Option<PathBuf>,
+    },
+    // Decrypt an env file into $VISUAL or $EDITOR and encrypt it again
+    Edit {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+    // Generate a key for encrypted env files
+    Keygen {
+        #[arg(short, long, value_name = "FILE", help = "Write to this file instead of EA_KEY_FILE or .env.key")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Overwrite an existing key file")]
+        force: bool,
+    },
+    // List the resolved variables with their source and a masked value
+    List {
+        #[arg(short, long, value_name = "FILE", help = "Path to config file")]
+        config: Option<PathBuf>,
+        #[arg(long, help = "Include every variable of the process environment")]
+        all: bool,
+        #[arg(long, help = "Print values unmasked")]
+        show: bool,
+    },
+    // Print the resolved value of a variable
+    Get {
+        #[arg(value_name = "NAME")]
+        name: String,
+        #[arg(short, long, value_name = "FILE", help = "Path to config file")]
+        config: Option<PathBuf>,
+    },
+    // Print the resolved variables, excluding the rest of the process environment
+    Export {
+        #[arg(short, long, value_name = "FILE", help = "Path to config file")]
+        config: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "FORMAT",
+            value_parser = ["shell", "docker", "json"],
+            default_value = "shell",
+            help = "Output format"
+        )]
+        format: String,
+    },
+}
+
+// Filters narrowing down which locations are touched, all of them are repeatable
+#[derive(Args)]
+struct SelectionArgs {
+    #[arg(long = "tag", value_name = "TAG", help = "Only locations with this tag")]
+    tags: Vec<String>,
+    #[arg(long = "exclude-tag", value_name = "TAG", help = "Skip locations with this tag")]
+    exclude_tags: Vec<String>,
+    #[arg(long = "id", value_name = "ID", help = "Only the location with this id")]
+    ids: Vec<String>,
+    #[arg(long = "variable", value_name = "NAME", help = "Only locations writing this variable")]
+    variables: Vec<String>,
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_parser = ["json", "toml", "yaml", "properties", "xml", "hocon"],
+        help = "Only locations of this file format"
+    )]
+    formats: Vec<String>,
+    #[arg(long = "file", value_name = "FILE", help = "Only touch this file")]
+    files: Vec<PathBuf>,
+}
+
+impl From<&SelectionArgs> for app::Selection {
+    fn from(args: &SelectionArgs) -> Self {
+        app::Selection {
+            tags: args.tags.clone(),
+            exclude_tags: args.exclude_tags.clone(),
+            ids: args.ids.clone(),
+            variables: args.variables.clone(),
+            formats: args.formats.clone(),
+            files: args.files.clone(),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    let global = &cli.global;
 
-    env::load().expect("Failed to load environment files");
     match &cli.command {
-        Commands::Apply { config } => handle_apply(config).await,
-        Commands::Deapply { config } => handle_deapply(config).await,
-        Commands::Parse { config } => handle_parse(config).await,
-        Commands::Files { config } => handle_files(config).await,
+        Commands::Apply { config, selection } => {
+            handle_apply(config, global, &selection.into()).await
+        }
+        Commands::Deapply { config, selection } => {
+            handle_deapply(config, global, &selection.into()).await
+        }
+        Commands::Parse { config, output } => handle_parse(config, global, output == "json").await,
+        Commands::Config {
+            command: ConfigCommands::Show { config, resolved, format, explain },
+        } => handle_config_show(config, global, *resolved, format, *explain).await,
+        Commands::Config {
+            command: ConfigCommands::Migrate { config, check },
+        } => handle_config_migrate(config, *check).await,
+        Commands::Schema { output } => handle_schema(output).await,
+        Commands::Env {
+            command: EnvCommands::Encrypt { file, output },
+        } => handle_env_encrypt(file, output).await,
+        Commands::Env {
+            command: EnvCommands::Decrypt { file, output },
+        } => handle_env_decrypt(file, output).await,
+        Commands::Env {
+            command: EnvCommands::Edit { file },
+        } => handle_env_edit(file).await,
+        Commands::Env {
+            command: EnvCommands::Keygen { output, force },
+        } => handle_env_keygen(output, *force).await,
+        Commands::Env {
+            command: EnvCommands::List { config, all, show },
+        } => handle_env_list(config, global, *all, *show).await,
+        Commands::Env {
+            command: EnvCommands::Get { name, config },
+        } => handle_env_get(config, global, name).await,
+        Commands::Env {
+            command: EnvCommands::Export { config, format },
+        } => handle_env_export(config, global, format).await,
+        Commands::Files { config, selection } => {
+            handle_files(config, global, &selection.into()).await
+        }
     }?;
 
     Ok(())
 }
 
-async fn handle_apply(config: &Option<PathBuf>) -> anyhow::Result<()> {
+/// Loads the config, applies the selected profile, if any, and then loads the environment.
+///
+/// The environment is loaded last as the config and profile can list additional env files.
+async fn load_config(
+    path: Option<PathBuf>,
+    global: &GlobalArgs,
+) -> anyhow::Result<(config::Config, env::Resolver)> {
+    let mut cfg = config::load_config(path).await?;
+    for file in cfg.include_tree.unversioned() {
+        eprintln!(
+            "Warning: {} has no `version`, run `ea config migrate` to add it",
+            file.display()
+        );
+    }
+    for (file, pattern) in cfg.include_tree.unmatched() {
+        eprintln!("Warning: `{}` in {} matches no files", pattern, file.display());
+    }
+    if let Some(name) = global.profile() {
+        cfg.apply_profile(&name)?;
+    }
+    let env = env::load(&env::Options {
+        mode: global.mode.clone(),
+        no_dotenv: global.no_dotenv,
+        config_files: cfg.environment.env_files.clone(),
+        env_files: global.env_files.clone(),
+        overrides: global.overrides.clone(),
+        secret_dirs: cfg.environment.secret_dirs(),
+        vault: cfg.sources.vault.clone(),
+        vault_references: app::referenced_variables(&cfg)
+            .into_iter()
+            .filter(|name| name.starts_with(env::vault::REFERENCE_PREFIX))
+            .collect(),
+        commands: cfg.sources.cmd.clone(),
+    })?;
+
+    Ok((cfg, env))
+}
+
+async fn handle_apply(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    selection: &app::Selection,
+) -> anyhow::Result<()> {
     println!("Applying configuration...");
     let potential_config = if let Some(path) = config {
         println!("Using config file: {}", path.display());
@@ -90,15 +370,29 @@ async fn handle_apply(config: &Option<PathBuf>) -> anyhow::Result<()> {
         println!("Using default configuration");
         None
     };
+    if let Some(name) = global.profile() {
+        println!("Using profile: {}", name);
+    }
 
-    let cfg = config::load_config(potential_config).await?;
-    let changes = app::apply(&cfg).await?;
-    println!("Applied {} changes", changes);
+    let (mut cfg, env) = load_config(potential_config, global).await?;
+    app::interpolate_paths(&mut cfg, &env, selection)?;
+    let summary = app::apply(&cfg, &env, selection).await?;
+    println!("Applied {} changes", summary.changes);
+    if !summary.missing.is_empty() {
+        println!("Missing variables:");
+        for missing in &summary.missing {
+            println!("  {}", missing);
+        }
+    }
 
     Ok(())
 }
 
-async fn handle_deapply(config: &Option<PathBuf>) -> anyhow::Result<()> {
+async fn handle_deapply(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    selection: &app::Selection,
+) -> anyhow::Result<()> {
     println!("Deapplying configuration...");
     let potential_config = if let Some(path) = config {
         println!("Using config file: {}", path.display());
@@ -107,15 +401,40 @@ async fn handle_deapply(config: &Option<PathBuf>) -> anyhow::Result<()> {
         println!("Using default configuration");
         None
     };
+    if let Some(name) = global.profile() {
+        println!("Using profile: {}", name);
+    }
 
-    let cfg = config::load_config(potential_config).await?;
-    let changes = app::deapply(&cfg).await?;
+    let (mut cfg, env) = load_config(potential_config, global).await?;
+    app::interpolate_paths(&mut cfg, &env, selection)?;
+    let changes = app::deapply(&cfg, &env, selection).await?;
     println!("Deapplied {} changes", changes);
 
     Ok(())
 }
 
-async fn handle_parse(config: &Option<PathBuf>) -> anyhow::Result<()> {
+async fn handle_parse(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        let diagnostics = match load_config(config.clone(), global).await {
+            Ok((mut cfg, env)) => {
+                // Paths which cannot be expanded are reported by `validate`
+                let _ = app::interpolate_paths(&mut cfg, &env, &app::Selection::default());
+                app::validate(&cfg, &env).await
+            }
+            Err(e) => {
+                let diagnostic = serde_json::json!({ "severity": "error", "message": e.to_string() });
+                println!("{}", serde_json::to_string_pretty(&[diagnostic])?);
+                return Err(e);
+            }
+        };
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+        return check_diagnostics(&diagnostics);
+    }
+
     println!("Parsing configuration...");
     let potential_config = if let Some(path) = config {
         println!("Using config file: {}", path.display());
@@ -124,38 +443,394 @@ async fn handle_parse(config: &Option<PathBuf>) -> anyhow::Result<()> {
         println!("Using default configuration");
         None
     };
+    if let Some(name) = global.profile() {
+        println!("Using profile: {}", name);
+    }
+
+    let (mut cfg, env) = load_config(potential_config, global).await?;
+    // Paths which cannot be expanded are reported by `validate`
+    let _ = app::interpolate_paths(&mut cfg, &env, &app::Selection::default());
+    println!("Resolved includes:\n{}", cfg.include_tree);
+
+    println!("Environment sources:");
+    for layer in env.layers() {
+        println!("  {} ({} variables)", layer.name(), layer.keys().len());
+    }
+    println!("Variables:");
+    for name in app::referenced_variables(&cfg) {
+        match app::resolve_variable(&cfg, &env, &name) {
+            Some(resolution) => println!("  {}: {}", name, resolution.source),
+            None => println!("  {}: not set", name),
+        }
+    }
 
-    config::load_config(potential_config).await?;
+    let diagnostics = app::validate(&cfg, &env).await;
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    check_diagnostics(&diagnostics)?;
     println!("The config has been validated.");
 
     Ok(())
 }
 
-async fn handle_files(config: &Option<PathBuf>) -> anyhow::Result<()> {
-    let potential_config = if let Some(path) = config {
-        Some(path.to_path_buf())
+/// Fails if any of the diagnostics is an error.
+fn check_diagnostics(diagnostics: &[app::Diagnostic]) -> anyhow::Result<()> {
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == app::Severity::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!("The config has {} error(s)", errors);
+    }
+
+    Ok(())
+}
+
+async fn handle_files(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    selection: &app::Selection,
+) -> anyhow::Result<()> {
+    let potential_config = config.as_ref().map(|path| path.to_path_buf());
+
+    let (mut cfg, env) = load_config(potential_config, global).await?;
+    app::interpolate_paths(&mut cfg, &env, selection)?;
+    let files = app::get(&cfg, &env, selection).await?;
+    if files.is_empty() {
+        println!();
     } else {
-        None
+        let mut output = String::with_capacity(files.len() * 80);
+        for file in files {
+            output.push_str(file.as_str());
+            output.push('\n');
+        }
+        print!("{}", output);
+    }
+
+    Ok(())
+}
+
+async fn handle_config_show(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    resolved: bool,
+    format: &str,
+    explain: bool,
+) -> anyhow::Result<()> {
+    let (mut cfg, env) = load_config(config.clone(), global).await?;
+    if resolved {
+        // References which cannot be resolved are shown as written
+        let _ = app::interpolate_paths(&mut cfg, &env, &app::Selection::default());
+    }
+    let format = match format {
+        "yaml" => config::Format::Yaml,
+        "json" => config::Format::Json,
+        _ => config::Format::Toml,
     };
 
-    let cfg = config::load_config(potential_config).await?;
-    match app::get(&cfg).await {
-        Ok(files) => {
-            if files.is_empty() {
-                println!();
-            } else {
-                let mut output = String::with_capacity(files.len() * 80);
-                for file in files {
-                    output.push_str(file.as_str());
-                    output.push('\n');
-                }
-                print!("{}", output);
-            }
+    let origins = match explain {
+        true => Some(cfg.origins().await?),
+        false => None,
+    };
+    let shown = if resolved { cfg.resolved() } else { cfg };
+    print!("{}", shown.render(format, origins.as_ref())?);
+
+    Ok(())
+}
+
+async fn handle_config_migrate(config: &Option<PathBuf>, check: bool) -> anyhow::Result<()> {
+    let path = config.clone().unwrap_or_else(|| PathBuf::from("config.toml"));
+    let migrations = config::migrate::migrate(&path).await?;
+
+    let mut outdated = 0;
+    for migration in &migrations {
+        if migration.steps.is_empty() {
+            println!("{} is up to date", migration.path.display());
+            continue;
+        }
+
+        outdated += 1;
+        println!(
+            "{} {} from version {} to {}:",
+            if check { "Would migrate" } else { "Migrated" },
+            migration.path.display(),
+            migration.from,
+            config::migrate::CURRENT_VERSION
+        );
+        for step in &migration.steps {
+            println!("  {}", step);
+        }
+        if !check {
+            tokio::fs::write(&migration.path, &migration.content).await?;
+        }
+    }
+
+    if check && outdated > 0 {
+        anyhow::bail!("{} config file(s) need to be migrated", outdated);
+    }
+    Ok(())
+}
+
+async fn handle_schema(output: &Option<PathBuf>) -> anyhow::Result<()> {
+    let schema = serde_json::to_string_pretty(&config::schema::generate())?;
+
+    match output {
+        Some(path) => tokio::fs::write(path, schema + "\n").await?,
+        None => println!("{}", schema),
+    }
+
+    Ok(())
+}
+
+async fn handle_env_encrypt(file: &Path, output: &Option<PathBuf>) -> anyhow::Result<()> {
+    let key = Key::load(|name| std::env::var(name).ok())?;
+    let plaintext = tokio::fs::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    env::parser::parse_entries(&plaintext)
+        .map_err(|e| anyhow::anyhow!("{}:{}: {}", file.display(), e.line, e.message))?;
+
+    let output = output.clone().unwrap_or_else(|| {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".enc");
+        PathBuf::from(name)
+    });
+    tokio::fs::write(&output, key.encrypt(&plaintext)).await?;
+    println!("Encrypted {} to {}", file.display(), output.display());
+
+    Ok(())
+}
+
+async fn handle_env_decrypt(file: &Path, output: &Option<PathBuf>) -> anyhow::Result<()> {
+    let output = match output {
+        Some(output) => output.clone(),
+        None if crypt::is_encrypted(file) => file.with_extension(""),
+        None => anyhow::bail!("{} has no .enc extension, pass --output", file.display()),
+    };
+
+    let plaintext = decrypt_file(file, &Key::load(|name| std::env::var(name).ok())?).await?;
+    write_private(&output, &plaintext)?;
+    println!("Decrypted {} to {}", file.display(), output.display());
+
+    Ok(())
+}
+
+async fn handle_env_edit(file: &Path) -> anyhow::Result<()> {
+    let key = Key::load(|name| std::env::var(name).ok())?;
+    let plaintext = match file.exists() {
+        true => decrypt_file(file, &key).await?,
+        false => String::new(),
+    };
+
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("ea-");
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+    let temp = builder
+        .tempdir()
+        .context("Failed to create a temporary directory")?;
+    let path = temp.path().join(name.trim_end_matches(".enc"));
+    let edited = edit_in_editor(&path, &plaintext);
+    let edited = match edited {
+        Ok(edited) if edited != plaintext => edited,
+        result => {
+            // The plaintext must not outlive the edit, whatever happened in the editor
+            let _ = temp.close();
+            result?;
+            println!("{} was not changed", file.display());
+            return Ok(());
+        }
+    };
+
+    // Errors may quote the plaintext, and the edit is kept in the private directory to be fixed
+    if let Err(e) = env::parser::parse_entries(&edited) {
+        let dir = temp.keep();
+        anyhow::bail!(
+            "{} was not changed, line {}: {}. The edit was kept in {}, fix it and run \
+             `ea env encrypt {} --output {}`, then delete {}",
+            file.display(),
+            e.line,
+            env::loader::ENCRYPTED_ENTRY_ERROR,
+            path.display(),
+            path.display(),
+            file.display(),
+            dir.display()
+        );
+    }
+    let _ = temp.close();
+
+    tokio::fs::write(file, key.encrypt(&edited)).await?;
+    println!("Encrypted {}", file.display());
+
+    Ok(())
+}
+
+async fn handle_env_keygen(output: &Option<PathBuf>, force: bool) -> anyhow::Result<()> {
+    let path = output.clone().unwrap_or_else(crypt::key_file);
+    if path.exists() && !force {
+        anyhow::bail!("{} already exists, pass --force to replace it", path.display());
+    }
+
+    write_private(&path, &(Key::generate() + "\n"))?;
+    println!("Wrote a new key to {}, keep it out of version control", path.display());
+
+    Ok(())
+}
+
+async fn handle_env_list(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    all: bool,
+    show: bool,
+) -> anyhow::Result<()> {
+    let (cfg, env) = load_config(config.clone(), global).await?;
+    let referenced = app::referenced_variables(&cfg);
+    let vars = resolved_variables(&cfg, &env, &referenced, all);
+
+    let rows: Vec<[String; 4]> = vars
+        .into_iter()
+        .map(|(name, resolution)| {
+            let used = if referenced.contains(&name) { "yes" } else { "no" };
+            let value = if show { resolution.value } else { export::mask(&resolution.value) };
+            [name, resolution.source, used.to_string(), value]
+        })
+        .collect();
+    let header = ["NAME", "SOURCE", "USED", "VALUE"].map(String::from);
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
         }
-        Err(..) => {
-            println!();
+    }
+    for [name, source, used, value] in std::iter::once(&header).chain(&rows) {
+        println!(
+            "{:w0$}  {:w1$}  {:w2$}  {}",
+            name,
+            source,
+            used,
+            value,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+    let unset = referenced.iter().filter(|name| app::resolve_variable(&cfg, &env, name).is_none());
+    for name in unset {
+        match env.source_error(name) {
+            Some(error) => eprintln!("Warning: cannot read {}: {}", name, error),
+            None => eprintln!("Warning: {} is used by the config but not set", name),
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_env_get(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    name: &str,
+) -> anyhow::Result<()> {
+    let (cfg, env) = load_config(config.clone(), global).await?;
+    match app::resolve_variable(&cfg, &env, name) {
+        Some(resolution) => println!("{}", resolution.value),
+        None => match env.source_error(name).or_else(|| env.secret_error(name)) {
+            Some(error) => anyhow::bail!(error),
+            None => anyhow::bail!("{} is not set", name),
+        },
+    }
+
+    Ok(())
+}
+
+async fn handle_env_export(
+    config: &Option<PathBuf>,
+    global: &GlobalArgs,
+    format: &str,
+) -> anyhow::Result<()> {
+    let (cfg, env) = load_config(config.clone(), global).await?;
+    let format = match format {
+        "docker" => export::Format::Docker,
+        "json" => export::Format::Json,
+        _ => export::Format::Shell,
+    };
+
+    let referenced = app::referenced_variables(&cfg);
+    let vars = resolved_variables(&cfg, &env, &referenced, false)
+        .into_iter()
+        .map(|(name, resolution)| (name, resolution.value))
+        .collect();
+    print!("{}", export::render(&vars, format)?);
+
+    Ok(())
+}
+
+/// Resolves the loaded variables and those used by the config, which may fall back to the
+/// default of their `[environment] variables` entry.
+fn resolved_variables(
+    cfg: &config::Config,
+    env: &env::Resolver,
+    referenced: &[String],
+    all: bool,
+) -> BTreeMap<String, env::Resolution> {
+    let mut vars = export::collect(env, referenced, all);
+    for name in referenced {
+        if !vars.contains_key(name) {
+            if let Some(resolution) = app::resolve_variable(cfg, env, name) {
+                vars.insert(name.clone(), resolution);
+            }
         }
-    } ;
+    }
+    vars
+}
+
+/// Decrypts an env file, naming the file in errors.
+async fn decrypt_file(file: &Path, key: &Key) -> anyhow::Result<String> {
+    let content = tokio::fs::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    key.decrypt(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt {}: {}", file.display(), e))
+}
+
+/// Writes `content` to `path`, opens it in `$VISUAL` or `$EDITOR` and returns the edited content.
+fn edit_in_editor(path: &Path, content: &str) -> anyhow::Result<String> {
+    write_private(path, content)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut args = editor.split_whitespace();
+    let program = args.next().context("VISUAL or EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run editor `{}`", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor `{}` exited with {}", editor, status);
+    }
+
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// Writes a file only the current user can read.
+///
+/// The content goes to a new file next to `path` which then replaces it, so an existing file or
+/// symlink at `path` is neither followed nor left with its old permissions.
+fn write_private(path: &Path, content: &str) -> anyhow::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
 
+    let mut file = tempfile::Builder::new()
+        .prefix(".ea-")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to write file: {}", path.display()))?;
+    std::io::Write::write_all(&mut file, content.as_bytes())?;
+    file.persist(path)
+        .with_context(|| format!("Failed to write file: {}", path.display()))?;
     Ok(())
 }
@@ -0,0 +1,784 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{apply, deapply, get, interpolate_paths, resolve_variable, validate, ApplyError};
+    use crate::app::Selection;
+    use crate::{handle_files, Cli, Commands};
+    use clap::Parser;
+    use crate::config::{Config, OnMissing};
+    use crate::tests::env_with;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn yaml_config(file: &str, variables: &str, variable: &str) -> Config {
+        toml::from_str(&format!(
+            r#"
+            [environment]
+            variables = {}
+
+            [[specific.yaml.locations]]
+            file = "{}"
+            node = "database.password"
+            variable = "{}"
+            "#,
+            variables,
+            file.replace('\\', "/"),
+            variable
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_apply_fails_on_missing_required_variables() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "database:\n  password: old\n")?;
+        let env = env_with(&[("EA_TEST_028_PRESENT", "secret")]);
+
+        let config = yaml_config(
+            &file.to_string_lossy(),
+            r#"["EA_TEST_028_PRESENT", "EA_TEST_028_MISSING_A", { name = "EA_TEST_028_MISSING_B" }]"#,
+            "EA_TEST_028_PRESENT",
+        );
+
+        let error = apply(&config, &env, &Selection::default()).await.unwrap_err();
+        match error.downcast_ref::<ApplyError>() {
+            Some(ApplyError::MissingVariables(names)) => {
+                assert_eq!(
+                    names,
+                    &vec!["EA_TEST_028_MISSING_A", "EA_TEST_028_MISSING_B"]
+                )
+            }
+            other => panic!("Expected missing variables, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&file)?, "database:\n  password: old\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_uses_variable_default() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "database:\n  password: old\n")?;
+
+        let config = yaml_config(
+            &file.to_string_lossy(),
+            r#"[{ name = "EA_TEST_028_DEFAULTED", default = "fallback" }]"#,
+            "EA_TEST_028_DEFAULTED",
+        );
+
+        assert_eq!(apply(&config, &env_with(&[]), &Selection::default()).await?.changes, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "database:\n  password: \"fallback\"\n"
+        );
+
+        let resolution = resolve_variable(&config, &env_with(&[]), "EA_TEST_028_DEFAULTED").unwrap();
+        assert_eq!(resolution.value, "fallback");
+        assert_eq!(resolution.source, "default in config");
+        let env = env_with(&[("EA_TEST_028_DEFAULTED", "set")]);
+        let resolution = resolve_variable(&config, &env, "EA_TEST_028_DEFAULTED").unwrap();
+        assert_eq!(resolution.source, "test");
+        assert_eq!(resolve_variable(&config, &env, "EA_TEST_028_UNKNOWN"), None);
+        Ok(())
+    }
+
+    fn config_for(file: &std::path::Path, toml_input: &str) -> Config {
+        let file = file.to_string_lossy().replace('\\', "/");
+        toml::from_str(&toml_input.replace("{file}", &file)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_on_missing_error_aborts_before_writing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "a: old\nb: old\n")?;
+        let env = env_with(&[("EA_TEST_029_SET", "new")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "a"
+            variable = "EA_TEST_029_SET"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "b"
+            variable = "EA_TEST_029_UNSET"
+            on_missing = "error"
+            "#,
+        );
+
+        let error = apply(&config, &env, &Selection::default()).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ApplyError>(),
+            Some(ApplyError::MissingVariables(names)) if names == &vec!["EA_TEST_029_UNSET"]
+        ));
+        assert_eq!(fs::read_to_string(&file)?, "a: old\nb: old\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_on_missing_policies() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "a: old\nb: old\nc: old\n")?;
+
+        let config = config_for(
+            &file,
+            r#"
+            [environment]
+            on_missing = "placeholder"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "a"
+            variable = "EA_TEST_029_A"
+            on_missing = "default"
+            fallback = "fallback"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "b"
+            variable = "EA_TEST_029_B"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "c"
+            variable = "EA_TEST_029_C"
+            on_missing = "skip"
+            "#,
+        );
+
+        let summary = apply(&config, &env_with(&[]), &Selection::default()).await?;
+
+        assert_eq!(summary.changes, 2);
+        let policies: Vec<OnMissing> = summary.missing.iter().map(|m| m.policy).collect();
+        assert_eq!(
+            policies,
+            vec![OnMissing::Default, OnMissing::Placeholder, OnMissing::Skip]
+        );
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "a: \"fallback\"\nb: \"%EA_TEST_029_B%\"\nc: old\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_on_missing_default_requires_fallback() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "a: old\n")?;
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "a"
+            variable = "EA_TEST_029_NO_FALLBACK"
+            on_missing = "default"
+            "#,
+        );
+
+        let error = apply(&config, &env_with(&[]), &Selection::default()).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ApplyError>(),
+            Some(ApplyError::MissingFallback(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_value_template() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "url: old\n")?;
+        let env = env_with(&[("EA_TEST_030_HOST", "db.local"), ("EA_TEST_030_PORT", "3306")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "url"
+            value = "jdbc:mysql://${EA_TEST_030_HOST}:${EA_TEST_030_PORT}/app?ssl=true"
+            "#,
+        );
+
+        assert_eq!(get(&config, &env, &Selection::default()).await?.len(), 1);
+        assert_eq!(apply(&config, &env, &Selection::default()).await?.changes, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "url: \"jdbc:mysql://db.local:3306/app?ssl=true\"\n"
+        );
+
+        assert_eq!(deapply(&config, &env, &Selection::default()).await?, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "url: \"jdbc:mysql://${EA_TEST_030_HOST}:${EA_TEST_030_PORT}/app?ssl=true\"\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_value_template_with_missing_variable() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "url: old\n")?;
+        let env = env_with(&[("EA_TEST_030_SET", "set")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "url"
+            value = "$EA_TEST_030_SET/${EA_TEST_030_UNSET}"
+            "#,
+        );
+
+        assert!(get(&config, &env, &Selection::default()).await?.is_empty());
+        let summary = apply(&config, &env, &Selection::default()).await?;
+        assert_eq!(summary.changes, 0);
+        assert_eq!(summary.missing[0].variables, vec!["EA_TEST_030_UNSET"]);
+        assert_eq!(fs::read_to_string(&file)?, "url: old\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_before_formatting() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("secret.yml");
+        fs::write(&file, "data:\n  password: old\n")?;
+        let env = env_with(&[("EA_TEST_031_SECRET", "  hunter2\n")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "data.password"
+            variable = "EA_TEST_031_SECRET"
+            transform = ["trim", "base64"]
+            "#,
+        );
+
+        apply(&config, &env, &Selection::default()).await?;
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "data:\n  password: \"aHVudGVyMg==\"\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_typed_value_aborts_before_writing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "debug: false\nport: 80\n")?;
+        let env = env_with(&[("EA_TEST_032_DEBUG", "yes"), ("EA_TEST_032_PORT", "eighty")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "debug"
+            variable = "EA_TEST_032_DEBUG"
+            type = "bool"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "port"
+            variable = "EA_TEST_032_PORT"
+            type = "integer"
+            "#,
+        );
+
+        let error = apply(&config, &env, &Selection::default()).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value for EA_TEST_032_PORT (yaml)"
+        );
+        assert_eq!(fs::read_to_string(&file)?, "debug: false\nport: 80\n");
+
+        let env = env_with(&[("EA_TEST_032_DEBUG", "yes"), ("EA_TEST_032_PORT", "0080")]);
+        apply(&config, &env, &Selection::default()).await?;
+        assert_eq!(fs::read_to_string(&file)?, "debug: true\nport: 80\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_when_conditions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "a: old\nb: old\nc: old\nd: old\n")?;
+        let env = env_with(&[
+            ("EA_TEST_033_VALUE", "new"),
+            ("EA_TEST_033_SSL", "true"),
+            ("EA_TEST_033_EMPTY", ""),
+        ]);
+
+        let mut config = config_for(
+            &file,
+            r#"
+            [environment]
+            prefix = "%"
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "a"
+            variable = "EA_TEST_033_VALUE"
+            when = { env = "EA_TEST_033_SSL", equals = "true" }
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "b"
+            variable = "EA_TEST_033_VALUE"
+            when = { all = [{ exists = "EA_TEST_033_EMPTY" }, { not = { env = "EA_TEST_033_EMPTY" } }] }
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "c"
+            variable = "EA_TEST_033_VALUE"
+            when = { any = [{ exists = "EA_TEST_033_UNSET" }, { profile = "prod" }] }
+
+            [[specific.yaml.locations]]
+            file = "{file}"
+            node = "d"
+            variable = "EA_TEST_033_UNSET"
+            on_missing = "error"
+            when = { exists = "EA_TEST_033_UNSET" }
+
+            [profiles.prod]
+            "#,
+        );
+
+        assert_eq!(apply(&config, &env, &Selection::default()).await?.changes, 2);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "a: \"new\"\nb: \"new\"\nc: old\nd: old\n"
+        );
+
+        config.apply_profile("prod")?;
+        assert_eq!(apply(&config, &env, &Selection::default()).await?.changes, 3);
+        assert_eq!(deapply(&config, &env, &Selection::default()).await?, 3);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "a: \"%EA_TEST_033_VALUE%\"\nb: \"%EA_TEST_033_VALUE%\"\nc: \"%EA_TEST_033_VALUE%\"\nd: old\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_selection_filters() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        let other = temp_dir.path().join("other.yml");
+        fs::write(&file, "user: old\npass: old\ncache: old\n")?;
+        fs::write(&other, "pass: old\n")?;
+        let env = env_with(&[
+            ("EA_TEST_034_USER", "admin"),
+            ("EA_TEST_034_PASS", "rotated"),
+            ("EA_TEST_034_CACHE", "redis"),
+        ]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            id = "db-user"
+            tags = ["db"]
+            file = "{file}"
+            node = "user"
+            variable = "EA_TEST_034_USER"
+
+            [[specific.yaml.locations]]
+            id = "db-pass"
+            tags = ["db", "secret"]
+            file = ["{file}", "{other}"]
+            node = "pass"
+            variable = "EA_TEST_034_PASS"
+
+            [[specific.yaml.locations]]
+            tags = ["cache"]
+            file = "{file}"
+            node = "cache"
+            value = "redis://${EA_TEST_034_CACHE}"
+            "#
+            .replace("{other}", &other.to_string_lossy().replace('\\', "/"))
+            .as_str(),
+        );
+
+        assert_eq!(get(&config, &env, &Selection::default()).await?.len(), 2);
+
+        let secrets = Selection {
+            tags: vec!["db".to_string()],
+            exclude_tags: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply(&config, &env, &secrets).await?.changes, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "user: \"admin\"\npass: old\ncache: old\n"
+        );
+
+        let rotated = Selection {
+            variables: vec!["EA_TEST_034_PASS".to_string()],
+            files: vec![other.clone()],
+            ..Default::default()
+        };
+        assert_eq!(apply(&config, &env, &rotated).await?.changes, 1);
+        assert_eq!(fs::read_to_string(&other)?, "pass: \"rotated\"\n");
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "user: \"admin\"\npass: old\ncache: old\n"
+        );
+
+        let by_template_variable = Selection {
+            variables: vec!["EA_TEST_034_CACHE".to_string()],
+            formats: vec!["yaml".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply(&config, &env, &by_template_variable).await?.changes, 1);
+
+        let by_id = Selection {
+            ids: vec!["db-pass".to_string()],
+            formats: vec!["json".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply(&config, &env, &by_id).await?.changes, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_selection_limits_required_variables() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "pass: old
+cache: old
+")?;
+        let env = env_with(&[("PASS", "rotated")]);
+
+        let config = config_for(
+            &file,
+            r#"
+            [environment]
+            variables = ["PASS", "CACHE"]
+
+            [[specific.yaml.locations]]
+            id = "db-pass"
+            tags = ["db"]
+            file = "{file}"
+            node = "pass"
+            variable = "PASS"
+
+            [[specific.yaml.locations]]
+            tags = ["cache"]
+            file = "{file}"
+            node = "cache"
+            variable = "CACHE"
+            "#,
+        );
+
+        let error = apply(&config, &env, &Selection::default()).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ApplyError>(),
+            Some(ApplyError::MissingVariables(names)) if names == &vec!["CACHE"]
+        ));
+
+        let db = Selection {
+            tags: vec!["db".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply(&config, &env, &db).await?.changes, 1);
+        assert_eq!(fs::read_to_string(&file)?, "pass: \"rotated\"\ncache: old\n");
+
+        let typo = Selection {
+            ids: vec!["db-pass".to_string(), "db-passwd".to_string()],
+            ..Default::default()
+        };
+        let error = apply(&config, &env, &typo).await.unwrap_err();
+        assert_eq!(error.to_string(), "No location has the id: db-passwd");
+        assert!(deapply(&config, &env, &typo).await.is_err());
+        assert!(get(&config, &env, &typo).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_command_rejects_unknown_ids() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "pass: old\n")?;
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+                [[specific.yaml.locations]]
+                id = "db-pass"
+                file = "{}"
+                node = "pass"
+                variable = "EA_TEST_034_PASS"
+                "#,
+                file.to_string_lossy().replace('\\', "/")
+            ),
+        )?;
+
+        let config_arg = config_path.to_string_lossy().to_string();
+        for id in ["db-pass", "db-passwd"] {
+            let args = ["ea", "files", "--no-dotenv", "-c", &config_arg, "--id", id];
+            let cli = Cli::try_parse_from(args)?;
+            let Commands::Files { config, selection } = &cli.command else {
+                unreachable!("parsed a files command");
+            };
+            let result = handle_files(config, &cli.global, &selection.into()).await;
+            match id {
+                "db-pass" => result?,
+                _ => assert_eq!(
+                    result.unwrap_err().to_string(),
+                    "No location has the id: db-passwd"
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_interpolate_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("plugins/essentials"))?;
+        let file = temp_dir.path().join("plugins/essentials/config.yml");
+        fs::write(&file, "database:\n  password: old\n")?;
+        let home = temp_dir.path().to_string_lossy().replace('\\', "/");
+        let env = env_with(&[("EA_TEST_037_HOME", &home), ("EA_TEST_037_PASS", "secret")]);
+
+        let mut config = config_for(
+            &file,
+            r#"
+            [[specific.yaml.locations]]
+            file = "${EA_TEST_037_HOME}/plugins/${EA_TEST_037_PLUGIN:-essentials}/config.yml"
+            node = "${EA_TEST_037_SECTION:-database}.password"
+            variable = "EA_TEST_037_PASS"
+            "#,
+        );
+        interpolate_paths(&mut config, &env, &Selection::default())?;
+
+        let loc = &config.specific.yaml.locations[0];
+        assert_eq!(
+            loc.file[0].to_string_lossy().replace('\\', "/"),
+            file.to_string_lossy().replace('\\', "/")
+        );
+        assert_eq!(loc.node, vec!["database.password"]);
+        assert_eq!(apply(&config, &env, &Selection::default()).await?.changes, 1);
+        assert_eq!(fs::read_to_string(&file)?, "database:\n  password: \"secret\"\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_interpolate_paths_fails_on_unresolved_references() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            file = "${EA_TEST_037_MISSING_ROOT}/config.yml"
+            node = ["a", "${EA_TEST_037_MISSING_NODE}.b"]
+            variable = "EA_TEST_037_PASS"
+            "#,
+        )
+        .unwrap();
+
+        match interpolate_paths(&mut config, &env_with(&[]), &Selection::default()) {
+            Err(ApplyError::UnresolvedPaths(names)) => assert_eq!(
+                names,
+                vec!["EA_TEST_037_MISSING_ROOT", "EA_TEST_037_MISSING_NODE"]
+            ),
+            other => panic!("Expected unresolved paths, got {:?}", other),
+        }
+        assert_eq!(config.specific.yaml.locations[0].node, vec!["a", "${EA_TEST_037_MISSING_NODE}.b"]);
+
+        let diagnostics = validate(&config, &env_with(&[])).await;
+        assert!(diagnostics.iter().any(|d| d.message
+            == "Cannot resolve variables in the file or node paths of `EA_TEST_037_PASS`: \
+                EA_TEST_037_MISSING_ROOT, EA_TEST_037_MISSING_NODE"));
+    }
+
+    #[test]
+    fn test_interpolate_paths_only_expands_braced_references() -> Result<()> {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[specific.json.locations]]
+            file = "$HOME/schema.json"
+            node = ["$schema", "${EA_TEST_037_SECTION:-defs}.$id"]
+            variable = "EA_TEST_037_PASS"
+
+            [[specific.json.locations]]
+            file = "${EA_TEST_037_INACTIVE}/app.json"
+            node = "a"
+            variable = "EA_TEST_037_PASS"
+            when = { exists = "EA_TEST_037_INACTIVE" }
+
+            [[specific.json.locations]]
+            file = "${EA_TEST_037_UNSELECTED}/app.json"
+            node = "a"
+            variable = "EA_TEST_037_PASS"
+            tags = ["other"]
+            "#,
+        )?;
+
+        let env = env_with(&[]);
+        match interpolate_paths(&mut config.clone(), &env, &Selection::default()) {
+            Err(ApplyError::UnresolvedPaths(names)) => assert_eq!(names, vec!["EA_TEST_037_UNSELECTED"]),
+            other => panic!("Expected unresolved paths, got {:?}", other),
+        }
+
+        let selection = Selection {
+            exclude_tags: vec!["other".into()],
+            ..Default::default()
+        };
+        interpolate_paths(&mut config, &env, &selection)?;
+        let loc = &config.specific.json.locations[0];
+        assert_eq!(loc.file[0].to_string_lossy(), "$HOME/schema.json");
+        assert_eq!(loc.node, vec!["$schema", "defs.$id"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deapply_placeholder_templates() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.properties");
+        fs::write(&file, "db.user=root\ndb.pass=secret\ndb.host=localhost\n")?;
+
+        let config = config_for(
+            &file,
+            r#"
+            [environment]
+            prefix = "%"
+            suffix = "%"
+
+            [specific.properties]
+            placeholder = "${{{variable}}}"
+
+            [[specific.properties.locations]]
+            file = "{file}"
+            node = "db.user"
+            variable = "DB_USER"
+
+            [[specific.properties.locations]]
+            file = "{file}"
+            node = "db.pass"
+            variable = "DB_PASS"
+            placeholder = "{{{{ .Values.{variable} }}}}"
+
+            [[specific.properties.locations]]
+            file = "{file}"
+            node = "db.host"
+            variable = "DB_HOST"
+            placeholder = "@{variable}@"
+            default = "%%DB_HOST%%"
+            "#,
+        );
+
+        assert_eq!(deapply(&config, &env_with(&[]), &Selection::default()).await?, 3);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "db.user=${DB_USER}\ndb.pass={{ .Values.DB_PASS }}\ndb.host=%%DB_HOST%%"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_reads_only_the_given_resolver() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "database:\n  password: old\n")?;
+        std::env::set_var("EA_TEST_045_FROM_PROCESS", "process");
+
+        let config = yaml_config(&file.to_string_lossy(), "[]", "DB_PASS");
+        let env = env_with(&[("DB_PASS", "secret")]);
+
+        assert_eq!(apply(&config, &env, &Selection::default()).await?.changes, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "database:\n  password: \"secret\"\n"
+        );
+
+        let config = yaml_config(&file.to_string_lossy(), "[]", "EA_TEST_045_FROM_PROCESS");
+        let summary = apply(&config, &env, &Selection::default()).await?;
+        assert_eq!(summary.changes, 0);
+        assert_eq!(summary.missing.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_variable_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("app.yml");
+        fs::write(&file, "database:\n  password: old\n")?;
+        let secret = temp_dir.path().join("db_pass");
+        fs::write(&secret, "from-file\n")?;
+
+        let mut config: Config = toml::from_str(&format!(
+            r#"
+            [environment]
+            prefix = "<"
+            suffix = ">"
+
+            [[specific.yaml.locations]]
+            file = "{}"
+            node = "database.password"
+            variable_file = "{}"
+            "#,
+            file.to_string_lossy().replace('\\', "/"),
+            secret.to_string_lossy().replace('\\', "/"),
+        ))?;
+        config.check_locations()?;
+
+        assert_eq!(apply(&config, &env_with(&[]), &Selection::default()).await?.changes, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "database:\n  password: \"from-file\"\n"
+        );
+        assert_eq!(deapply(&config, &env_with(&[]), &Selection::default()).await?, 1);
+        assert_eq!(
+            fs::read_to_string(&file)?,
+            "database:\n  password: \"<db_pass>\"\n"
+        );
+
+        fs::remove_file(&secret)?;
+        let summary = apply(&config, &env_with(&[]), &Selection::default()).await?;
+        assert_eq!(summary.changes, 0);
+        assert_eq!(summary.missing.len(), 1);
+
+        config.specific.yaml.locations[0].variable = "DB_PASS".to_string();
+        assert!(config.check_locations().is_err());
+        Ok(())
+    }
+}
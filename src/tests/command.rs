@@ -0,0 +1,201 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::config::{CommandFormat, CommandSource};
+    use crate::env::command::CommandError;
+    use crate::env::{load, Options};
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn sh(script: &str) -> CommandSource {
+        CommandSource {
+            command: vec!["sh".into(), "-c".into(), script.into()],
+            format: CommandFormat::Text,
+            variable: None,
+            keys: BTreeMap::new(),
+            timeout: 5,
+        }
+    }
+
+    fn json(script: &str, keys: &[(&str, &str)]) -> CommandSource {
+        CommandSource {
+            format: CommandFormat::Json,
+            keys: keys
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..sh(script)
+        }
+    }
+
+    fn options(commands: Vec<(&str, CommandSource)>) -> Options {
+        Options {
+            no_dotenv: true,
+            commands: commands
+                .into_iter()
+                .map(|(name, source)| (name.to_string(), source))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_command_sources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let runs = temp_dir.path().join("runs");
+        let item = format!(
+            r#"echo run >> {}; echo '{{"username": "admin", "fields": [{{"value": "hunter2"}}], "port": 5432}}'"#,
+            runs.display()
+        );
+
+        let env = load(&options(vec![
+            ("EA_TEST_050_TEXT", sh("printf 'secret\\n'")),
+            (
+                "renamed",
+                CommandSource {
+                    variable: Some("EA_TEST_050_RENAMED".into()),
+                    ..sh("echo renamed")
+                },
+            ),
+            (
+                "item",
+                json(
+                    &item,
+                    &[
+                        ("EA_TEST_050_USER", "username"),
+                        ("EA_TEST_050_PASS", "/fields/0/value"),
+                    ],
+                ),
+            ),
+            ("item_port", json(&item, &[("EA_TEST_050_PORT", "port")])),
+        ]))?;
+
+        assert_eq!(env.get("EA_TEST_050_TEXT").as_deref(), Some("secret"));
+        assert_eq!(env.get("EA_TEST_050_RENAMED").as_deref(), Some("renamed"));
+        assert_eq!(env.get("EA_TEST_050_USER").as_deref(), Some("admin"));
+        assert_eq!(env.get("EA_TEST_050_PASS").as_deref(), Some("hunter2"));
+        assert_eq!(env.get("EA_TEST_050_PORT").as_deref(), Some("5432"));
+        assert_eq!(env.get("username"), None);
+        assert_eq!(
+            env.resolve("EA_TEST_050_TEXT").unwrap().source,
+            "cmd EA_TEST_050_TEXT (sh -c printf 'secret\\n')"
+        );
+        // Both JSON sources run the same command
+        assert_eq!(fs::read_to_string(&runs)?, "run\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_runs_on_demand() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let runs = temp_dir.path().join("runs");
+        let script = format!("echo run >> {}; echo value", runs.display());
+
+        let env = load(&Options {
+            overrides: vec![("EA_TEST_050_SET".into(), "set".into())],
+            ..options(vec![
+                ("EA_TEST_050_SET", sh("exit 1")),
+                ("EA_TEST_050_LAZY", sh(&script)),
+            ])
+        })?;
+        assert!(!runs.exists());
+
+        assert_eq!(env.get("EA_TEST_050_SET").as_deref(), Some("set"));
+        assert_eq!(env.source_error("EA_TEST_050_SET"), None);
+        assert_eq!(env.get("EA_TEST_050_LAZY").as_deref(), Some("value"));
+        assert_eq!(env.get("EA_TEST_050_LAZY").as_deref(), Some("value"));
+        assert_eq!(fs::read_to_string(&runs)?, "run\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_errors() -> Result<()> {
+        let error = |source: CommandSource| -> Result<String> {
+            let env = load(&options(vec![("EA_TEST_050_ERR", source)]))?;
+            assert_eq!(env.get("EA_TEST_050_ERR"), None);
+            Ok(env.source_error("EA_TEST_050_ERR").unwrap_or_default())
+        };
+
+        assert_eq!(
+            error(sh("echo 'not in the password store' >&2; exit 3"))?,
+            "`sh -c echo 'not in the password store' >&2; exit 3` failed with exit status: 3: \
+             not in the password store"
+        );
+        assert_eq!(
+            error(CommandSource {
+                timeout: 1,
+                ..sh("sleep 5")
+            })?,
+            "`sh -c sleep 5` timed out after 1s"
+        );
+        // A process left behind holding stdout open doesn't outlast the timeout
+        let started = std::time::Instant::now();
+        assert_eq!(
+            error(CommandSource {
+                timeout: 1,
+                ..sh("sleep 5 & echo value")
+            })?,
+            "`sh -c sleep 5 & echo value` timed out after 1s"
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(
+            error(json("echo '[1]'", &[("EA_TEST_050_ERR", "key")]))?,
+            "`sh -c echo '[1]'` did not print a JSON object: not an object"
+        );
+        assert_eq!(
+            error(json("echo '{}'", &[("EA_TEST_050_ERR", "missing")]))?,
+            "The output of `sh -c echo '{}'` has no `missing`"
+        );
+        assert!(error(CommandSource {
+            command: vec!["ea-test-050-missing-program".into()],
+            ..sh("")
+        })?
+        .starts_with("Failed to run `ea-test-050-missing-program`"));
+
+        let err = load(&options(vec![(
+            "db",
+            CommandSource {
+                command: Vec::new(),
+                ..sh("")
+            },
+        )]))
+        .unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(CommandError::Empty(name)) if name == "db"));
+        let err = load(&options(vec![("db-pass", sh("echo x"))])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[sources.cmd.db-pass] sets `db-pass`, which is not a valid variable name"
+        );
+        let err = load(&options(vec![("item", json("echo '{}'", &[]))])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[sources.cmd.item] has `format = \"json\"` but no `keys` to map its output"
+        );
+        Ok(())
+    }
+}
@@ -24,7 +24,7 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::config::Config;
+    use crate::config::{Config, Placeholder};
     use std::path::PathBuf;
 
     const SAMPLE_CONFIG: &str = r##"
@@ -58,7 +58,9 @@ mod tests {
         // Test environment section
         assert_eq!(config.environment.prefix, "test");
         assert_eq!(config.environment.suffix, "test");
-        assert_eq!(config.environment.variables, vec!["APP_ENV", "DEBUG"]);
+        let names: Vec<&str> =
+            config.environment.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["APP_ENV", "DEBUG"]);
 
         // Test yaml locations
         let yaml_locations = &config.specific.yaml.locations;
@@ -110,6 +112,123 @@ mod tests {
         assert!(!location.override_settings.exempt_deapply);
     }
 
+    #[test]
+    fn test_variable_entries() {
+        let toml_input = r#"
+            [environment]
+            variables = [
+                "PLAIN",
+                { name = "DB_PASS", description = "Database password" },
+                { name = "DB_PORT", default = "3306" },
+            ]
+        "#;
+
+        let config: Config = toml::from_str(toml_input).unwrap();
+        let variables = &config.environment.variables;
+
+        let names: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["PLAIN", "DB_PASS", "DB_PORT"]);
+        assert!(variables[0].description.is_none());
+        assert_eq!(variables[1].description.as_deref(), Some("Database password"));
+        assert!(variables[1].default.is_none());
+        assert_eq!(variables[2].default.as_deref(), Some("3306"));
+    }
+
+    #[test]
+    fn test_variable_or_value_required() {
+        let neither: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            file = "app.yml"
+            node = "url"
+        "#,
+        )
+        .unwrap();
+        assert!(neither.check_locations().is_err());
+
+        let both: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            variable = "DB_URL"
+            value = "${DB_HOST}"
+        "#,
+        )
+        .unwrap();
+        assert!(both.check_locations().is_err());
+
+        let template: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            value = "${DB_HOST}:${DB_PORT}"
+        "#,
+        )
+        .unwrap();
+        assert!(template.check_locations().is_ok());
+        assert!(template.specific.yaml.locations[0].variable.is_empty());
+    }
+
+    #[test]
+    fn test_when_conditions() {
+        let config: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            variable = "A"
+            when = { any = [{ env = "FEATURE", equals = "on" }, { not = { profile = "dev" } }] }
+        "#,
+        )
+        .unwrap();
+        let when = config.specific.yaml.locations[0].when.as_ref().unwrap();
+        let any = when.any.as_ref().unwrap();
+        assert_eq!(any[0].env.as_deref(), Some("FEATURE"));
+        assert_eq!(any[0].equals.as_deref(), Some("on"));
+        assert_eq!(any[1].not.as_ref().unwrap().profile.as_deref(), Some("dev"));
+        assert!(config.check_locations().is_ok());
+
+        let typo: Result<Config, _> = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            variable = "A"
+            when = { env = "FEATURE", equal = "on" }
+        "#,
+        );
+        assert!(typo.is_err());
+
+        let dangling: Config = toml::from_str(
+            r#"
+            [[specific.yaml.locations]]
+            variable = "A"
+            when = { not = { equals = "on" } }
+        "#,
+        )
+        .unwrap();
+        assert!(dangling.check_locations().is_err());
+    }
+
+    #[test]
+    fn test_placeholder_templates() {
+        let render = |template: &str| {
+            Placeholder::try_from(template.to_string()).map(|p| p.render("DB_PASS"))
+        };
+
+        assert_eq!(render("${{{variable}}}"), Ok("${DB_PASS}".to_string()));
+        assert_eq!(render("%{variable}%"), Ok("%DB_PASS%".to_string()));
+        assert_eq!(
+            render("{{{{ .Values.{variable} }}}}"),
+            Ok("{{ .Values.DB_PASS }}".to_string())
+        );
+        assert!(render("${variable").unwrap_err().contains("Unclosed"));
+        assert!(render("{name}").unwrap_err().contains("Unknown token `{name}`"));
+        assert!(render("%{variable}}").unwrap_err().contains("Unmatched"));
+
+        let result: Result<Config, _> = toml::from_str(
+            r#"
+            [specific.yaml]
+            placeholder = "{var}"
+            "#,
+        );
+        assert!(result.unwrap_err().to_string().contains("expected `{variable}`"));
+    }
+
     #[test]
     fn test_invalid_toml() {
         let invalid_toml = r#"
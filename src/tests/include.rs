@@ -0,0 +1,199 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{load_config, ConfigError};
+    use anyhow::Result;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn write(temp_dir: &TempDir, filename: &str, content: &str) -> Result<PathBuf> {
+        let path = temp_dir.path().join(filename);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    #[tokio::test]
+    async fn test_include_merges_in_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        write(
+            &temp_dir,
+            "shared/db.toml",
+            r#"
+            [environment]
+            prefix = "{{"
+            suffix = "}}"
+
+            [[specific.yaml.locations]]
+            file = "db.yml"
+            node = "database.password"
+            variable = "DB_PASS"
+            "#,
+        )?;
+        write(
+            &temp_dir,
+            "app/common/a.toml",
+            r#"
+            [environment]
+            suffix = "%"
+
+            [[specific.yaml.locations]]
+            file = "app.yml"
+            node = "server.port"
+            variable = "PORT"
+            "#,
+        )?;
+        let root = write(
+            &temp_dir,
+            "app/config.toml",
+            r#"
+            include = ["../shared/db.toml", "common/*.toml"]
+
+            [[specific.yaml.locations]]
+            file = "app.yml"
+            node = "server.host"
+            variable = "HOST"
+            "#,
+        )?;
+
+        let config = load_config(Some(root)).await?;
+
+        assert_eq!(config.environment.prefix, "{{");
+        assert_eq!(config.environment.suffix, "%");
+        let variables: Vec<&str> = config
+            .specific
+            .yaml
+            .locations
+            .iter()
+            .map(|l| l.variable.as_str())
+            .collect();
+        assert_eq!(variables, vec!["DB_PASS", "PORT", "HOST"]);
+        assert_eq!(
+            config.include,
+            vec![
+                PathBuf::from("../shared/db.toml"),
+                PathBuf::from("common/*.toml")
+            ]
+        );
+        assert_eq!(config.include_tree.children.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_including_file_wins() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        write(&temp_dir, "base.toml", "[environment]\nprefix = \"base\"\n")?;
+        let root = write(
+            &temp_dir,
+            "config.toml",
+            "extends = \"base.toml\"\n[environment]\nprefix = \"root\"\n",
+        )?;
+
+        let config = load_config(Some(root)).await?;
+
+        assert_eq!(config.environment.prefix, "root");
+        assert_eq!(config.environment.suffix, "%");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_include_cycle_is_detected() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        write(&temp_dir, "a.toml", "include = \"b.toml\"\n")?;
+        write(&temp_dir, "b.toml", "include = \"a.toml\"\n")?;
+        let root = write(&temp_dir, "config.toml", "include = \"a.toml\"\n")?;
+
+        let result = load_config(Some(root)).await;
+
+        match result {
+            Err(ConfigError::IncludeCycle(cycle)) => {
+                assert!(cycle.contains("a.toml -> "));
+                assert!(cycle.ends_with("a.toml"));
+            }
+            other => panic!("Expected an include cycle error, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_include_tree_display() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        write(&temp_dir, "shared.toml", "")?;
+        write(&temp_dir, "a.toml", "include = \"shared.toml\"\n")?;
+        let root = write(
+            &temp_dir,
+            "config.toml",
+            "include = [\"a.toml\", \"shared.toml\"]\n",
+        )?;
+
+        let config = load_config(Some(root.clone())).await?;
+        let dir = temp_dir.path();
+        let expected = format!(
+            "{}\n├── {}\n│   └── {}\n└── {} (already included)",
+            root.display(),
+            dir.join("a.toml").display(),
+            dir.join("shared.toml").display(),
+            dir.join("shared.toml").display(),
+        );
+
+        assert_eq!(config.include_tree.to_string(), expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_include() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = write(&temp_dir, "config.toml", "include = \"missing.toml\"\n")?;
+
+        let result = load_config(Some(root)).await;
+
+        assert!(matches!(result, Err(ConfigError::NotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_include_glob() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        write(&temp_dir, "conf.d/a.toml", "")?;
+        let root = write(
+            &temp_dir,
+            "config.toml",
+            "include = [\"conf.d/*.toml\", \"local/*.toml\"]\n",
+        )?;
+
+        let config = load_config(Some(root.clone())).await?;
+
+        assert_eq!(config.include_tree.children.len(), 1);
+        assert_eq!(
+            config.include_tree.unmatched(),
+            vec![(root.as_path(), "local/*.toml")]
+        );
+        Ok(())
+    }
+}
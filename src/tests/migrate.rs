@@ -0,0 +1,106 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::migrate::{migrate, migrate_content, CURRENT_VERSION};
+    use crate::config::{load_config, Config, ConfigError};
+    use anyhow::Result;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migrate_unversioned_config() -> Result<()> {
+        let content = r#"#:schema ./ea.schema.json
+# Shared settings
+[environment]
+prefix = "%%" # Minecraft style
+
+[[specific.yaml.locations]]
+file = "config.yml"
+node = "database.password"
+variable = "DB_PASS"
+"#;
+
+        let migration = migrate_content(Path::new("config.toml"), content)?;
+        assert_eq!(migration.from, 0);
+        assert_eq!(migration.steps, vec!["Add the `version` field"]);
+        assert_eq!(
+            migration.content,
+            content.replace(
+                "# Shared settings\n",
+                &format!("# Shared settings\nversion = {}\n\n", CURRENT_VERSION)
+            )
+        );
+
+        let config: Config = toml::from_str(&migration.content)?;
+        assert_eq!(config.version, Some(CURRENT_VERSION));
+        assert_eq!(config.environment.prefix, "%%");
+
+        // Migrating again is a no-op
+        let again = migrate_content(Path::new("config.toml"), &migration.content)?;
+        assert!(again.steps.is_empty());
+        assert_eq!(again.content, migration.content);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_include_tree() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("shared.toml"), "version = 1\n")?;
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, "include = \"shared.toml\"\n")?;
+
+        let config = load_config(Some(config_path.clone())).await?;
+        assert_eq!(
+            config.include_tree.unversioned(),
+            vec![config_path.as_path()]
+        );
+
+        let migrations = migrate(&config_path).await?;
+        assert_eq!(migrations.len(), 2);
+        assert!(migrations[0].steps.is_empty());
+        assert_eq!(
+            migrations[1].content,
+            "version = 1\n\ninclude = \"shared.toml\"\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_newer_version_is_rejected() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, format!("version = {}\n", CURRENT_VERSION + 1))?;
+
+        let result = load_config(Some(config_path.clone())).await;
+        assert!(matches!(result, Err(ConfigError::UnsupportedVersion(_))));
+        assert!(matches!(
+            migrate(&config_path).await,
+            Err(ConfigError::UnsupportedVersion(_))
+        ));
+        Ok(())
+    }
+}
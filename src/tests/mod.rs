@@ -22,5 +22,27 @@
  * SOFTWARE.
  */
 
+mod app;
+mod command;
 mod config;
+mod env;
 mod fileformats;
+mod include;
+mod migrate;
+mod profile;
+mod schema;
+mod show;
+mod transform;
+mod validate;
+mod value;
+mod vault;
+
+use crate::env::{Resolver, VarMap};
+
+/// Builds a resolver holding only `vars`, so tests never depend on the process environment.
+fn env_with(vars: &[(&str, &str)]) -> Resolver {
+    Resolver::new(vec![Box::new(VarMap::new(
+        "test",
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+    ))])
+}
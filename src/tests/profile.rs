@@ -0,0 +1,129 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, ConfigError};
+
+    const SAMPLE_CONFIG: &str = r##"
+        [environment]
+        prefix = "%"
+        suffix = "%"
+
+        [[specific.yaml.locations]]
+        id = "db-password"
+        file = "app.yml"
+        node = "database.password"
+        variable = "DB_PASS"
+
+        [[specific.yaml.locations]]
+        id = "db-host"
+        file = "app.yml"
+        node = "database.host"
+        variable = "DB_HOST"
+
+        [profiles.staging]
+        environment = { prefix = "${", suffix = "}" }
+        exempt = ["db-password"]
+
+            [[profiles.staging.specific.properties.locations]]
+            file = "staging.properties"
+            node = "debug"
+            variable = "DEBUG"
+
+        [profiles.production]
+    "##;
+
+    #[test]
+    fn test_profile_overrides() {
+        let mut config: Config = toml::from_str(SAMPLE_CONFIG).unwrap();
+        config.apply_profile("staging").unwrap();
+
+        assert_eq!(config.active_profile.as_deref(), Some("staging"));
+        assert_eq!(config.environment.prefix, "${");
+        assert_eq!(config.environment.suffix, "}");
+
+        let yaml = &config.specific.yaml.locations;
+        assert!(yaml[0].override_settings.exempt_apply);
+        assert!(yaml[0].override_settings.exempt_deapply);
+        assert!(!yaml[1].override_settings.exempt_apply);
+
+        let properties = &config.specific.properties.locations;
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].variable, "DEBUG");
+    }
+
+    #[test]
+    fn test_empty_profile_keeps_config() {
+        let mut config: Config = toml::from_str(SAMPLE_CONFIG).unwrap();
+        let original = config.clone();
+        config.apply_profile("production").unwrap();
+
+        assert_eq!(config.environment, original.environment);
+        assert_eq!(config.specific, original.specific);
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let mut config: Config = toml::from_str(SAMPLE_CONFIG).unwrap();
+
+        match config.apply_profile("qa") {
+            Err(ConfigError::UnknownProfile(message)) => {
+                assert_eq!(message, "qa (available: production, staging)")
+            }
+            other => panic!("Expected an unknown profile error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_profile_exempting_unknown_id() {
+        let toml_input = r#"
+            [profiles.broken]
+            exempt = ["missing"]
+        "#;
+        let mut config: Config = toml::from_str(toml_input).unwrap();
+
+        let result = config.apply_profile("broken");
+        assert!(matches!(result, Err(ConfigError::UnknownLocation(_))));
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let toml_input = r#"
+            [[specific.json.locations]]
+            id = "same"
+            variable = "A"
+
+            [[specific.toml.locations]]
+            id = "same"
+            variable = "B"
+        "#;
+        let config: Config = toml::from_str(toml_input).unwrap();
+
+        assert!(matches!(
+            config.check_ids(),
+            Err(ConfigError::DuplicateId(_))
+        ));
+    }
+}
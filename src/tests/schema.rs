@@ -0,0 +1,110 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::schema::generate;
+    use serde_json::{json, Value};
+
+    fn schema() -> Value {
+        serde_json::to_value(generate()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_draft() {
+        let schema = schema();
+
+        assert_eq!(
+            schema["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        assert_eq!(schema["title"], "Env Applier configuration");
+        for name in [
+            "Environment",
+            "Specific",
+            "FileTypeConfig",
+            "Location",
+            "OverrideSettings",
+        ] {
+            assert!(
+                schema["$defs"].get(name).is_some(),
+                "missing definition {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_string_or_list() {
+        let schema = schema();
+        let expected = json!([
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } }
+        ]);
+
+        let location = &schema["$defs"]["Location"]["properties"];
+        assert_eq!(location["file"]["anyOf"], expected);
+        assert_eq!(location["node"]["anyOf"], expected);
+        assert_eq!(location["files"]["anyOf"], expected);
+        assert_eq!(location["nodes"]["anyOf"], expected);
+        assert_eq!(schema["properties"]["include"]["anyOf"], expected);
+        assert_eq!(schema["properties"]["extends"]["anyOf"], expected);
+    }
+
+    #[test]
+    fn test_schema_enums_and_renames() {
+        let schema = schema();
+        let defs = &schema["$defs"];
+
+        let location = &defs["Location"]["properties"];
+        assert!(location.get("type").is_some());
+        assert!(location.get("override").is_some());
+
+        let settings = &defs["OverrideSettings"]["properties"];
+        assert!(settings.get("exemptApply").is_some());
+        assert!(settings.get("exemptDeapply").is_some());
+
+        let on_missing = serde_json::to_string(&defs["OnMissing"]).unwrap();
+        for name in ["skip", "error", "default", "placeholder"] {
+            assert!(
+                on_missing.contains(&format!("\"{}\"", name)),
+                "missing {}",
+                name
+            );
+        }
+
+        let transform = serde_json::to_string(&defs["Transform"]).unwrap();
+        assert!(transform.contains("\"base64-decode\""));
+        assert!(transform.contains("\"json-escape\""));
+    }
+
+    #[test]
+    fn test_schema_variable_entries() {
+        let schema = schema();
+        let variable = serde_json::to_string(&schema["$defs"]["Variable"]).unwrap();
+
+        assert!(variable.contains("\"type\":\"string\""));
+        assert!(variable.contains("VariableTable"));
+    }
+}
@@ -0,0 +1,201 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{load_config, Config, Format, OnMissing};
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_configs(temp_dir: &TempDir) -> Result<std::path::PathBuf> {
+        fs::write(
+            temp_dir.path().join("base.toml"),
+            r#"[environment]
+prefix = "{"
+
+[[specific.yaml.locations]]
+file = "app.yml"
+node = "database.password"
+variable = "DB_PASS"
+
+[[specific.yaml.locations]]
+file = "app.yml"
+node = "database.user"
+variable = "DB_USER"
+default = "admin"
+"#,
+        )?;
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"include = "base.toml"
+
+[environment]
+suffix = "}"
+on_missing = "error"
+
+[specific.properties]
+placeholder = "${{{variable}}}"
+
+[[specific.properties.locations]]
+files = "app.properties"
+nodes = "db.url"
+value = "jdbc:${DB_HOST}"
+
+[[specific.properties.locations]]
+file = "app.properties"
+node = "db.user"
+variable = "DB_USER"
+on_missing = "skip"
+
+[profiles.prod]
+environment = { on_missing = "placeholder" }
+"#,
+        )?;
+        Ok(config_path)
+    }
+
+    #[tokio::test]
+    async fn test_resolved_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut config = load_config(Some(write_configs(&temp_dir)?)).await?;
+        config.apply_profile("prod")?;
+        config.environment.secret_dirs = Some(vec!["secrets".into()]);
+        config.specific.properties.locations[1].variable.clear();
+        config.specific.properties.locations[1].variable_file = Some("db_user.txt".into());
+
+        let resolved = config.resolved();
+        assert!(resolved.include.is_empty());
+        assert!(resolved.profiles.is_empty());
+        assert!(resolved.environment.secret_dirs.as_ref().unwrap()[0].is_absolute());
+        let variable_file = resolved.specific.properties.locations[1].variable_file.as_ref();
+        assert!(variable_file.unwrap().is_absolute());
+
+        let password = &resolved.specific.yaml.locations[0];
+        assert!(password.file[0].is_absolute());
+        assert_eq!(password.on_missing, Some(OnMissing::Placeholder));
+        assert_eq!(
+            password.placeholder.as_ref().unwrap().to_string(),
+            "{{{variable}}}"
+        );
+        let user = &resolved.specific.yaml.locations[1];
+        assert_eq!(user.placeholder, None);
+        assert_eq!(user.default.as_deref(), Some("admin"));
+
+        let url = &resolved.specific.properties.locations[0];
+        assert_eq!(url.placeholder, None);
+        let user = &resolved.specific.properties.locations[1];
+        assert_eq!(user.on_missing, Some(OnMissing::Skip));
+        assert_eq!(
+            user.placeholder.as_ref().unwrap().render("DB_USER"),
+            "${DB_USER}"
+        );
+
+        // The resolved output is itself a valid config describing the same locations
+        let reparsed: Config = toml::from_str(&resolved.render(Format::Toml, None)?)?;
+        assert_eq!(reparsed.specific.properties, {
+            let mut properties = resolved.specific.properties.clone();
+            properties
+                .locations
+                .iter_mut()
+                .for_each(|loc| loc.origin = None);
+            properties
+        });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain_origins() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut config = load_config(Some(write_configs(&temp_dir)?)).await?;
+        config.apply_profile("prod")?;
+
+        let origins = config.origins().await?;
+        let file_of = |key: &str| {
+            let origin = &origins[key];
+            origin
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or(origin)
+                .to_string()
+        };
+        assert_eq!(file_of("environment.prefix"), "base.toml:2:1");
+        assert_eq!(file_of("environment.suffix"), "config.toml:4:1");
+        assert_eq!(file_of("environment.on_missing"), "config.toml:22:17");
+        assert_eq!(file_of("environment.variables"), "default");
+        assert_eq!(
+            file_of("specific.properties.placeholder"),
+            "config.toml:8:1"
+        );
+        assert_eq!(file_of("specific.yaml.locations.0"), "base.toml:4:1");
+        assert_eq!(
+            file_of("specific.properties.locations.1"),
+            "config.toml:15:1"
+        );
+
+        let toml = config.resolved().render(Format::Toml, Some(&origins))?;
+        assert!(toml.contains(&format!(
+            "suffix = \"}}\" # {}",
+            origins["environment.suffix"]
+        )));
+        assert!(toml.contains(&format!(
+            "# {}\n[[specific.yaml.locations]]",
+            origins["specific.yaml.locations.0"]
+        )));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&config.render(Format::Json, Some(&origins))?)?;
+        assert_eq!(json["config"]["environment"]["suffix"], "}");
+        assert_eq!(json["origins"]["environment.variables"], "default");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain_table_origins() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"[[environment.variables]]
+name = "DB_HOST"
+
+[[environment.variables]]
+name = "DB_PASS"
+description = "Database password"
+"#,
+        )?;
+        let config = load_config(Some(config_path)).await?;
+
+        let origins = config.origins().await?;
+        assert!(origins["environment.variables"].ends_with("config.toml:1:15"));
+
+        let toml = config.resolved().render(Format::Toml, Some(&origins))?;
+        assert!(toml.contains(&format!(
+            "# {}\n[[environment.variables]]",
+            origins["environment.variables"]
+        )));
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::app::transform::apply_all;
+    use crate::config::{Config, Transform};
+    use anyhow::Result;
+
+    #[test]
+    fn test_transform_names() {
+        let toml_input = r#"
+            [[specific.yaml.locations]]
+            variable = "SECRET"
+            transform = ["trim", "lowercase", "upper", "base64", "base64-decode", "hex", "hex-decode", "url-encode", "sha256", "bcrypt", "json-escape"]
+        "#;
+
+        let config: Config = toml::from_str(toml_input).unwrap();
+
+        assert_eq!(
+            config.specific.yaml.locations[0].transform,
+            vec![
+                Transform::Trim,
+                Transform::Lower,
+                Transform::Upper,
+                Transform::Base64,
+                Transform::Base64Decode,
+                Transform::Hex,
+                Transform::HexDecode,
+                Transform::Urlencode,
+                Transform::Sha256,
+                Transform::Bcrypt,
+                Transform::JsonEscape,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encoding_transforms() -> Result<()> {
+        let run = |transforms: &[Transform], value: &str| apply_all(transforms, value.to_string());
+
+        assert_eq!(
+            run(&[Transform::Trim, Transform::Upper], "  Secret \n")?,
+            "SECRET"
+        );
+        assert_eq!(run(&[Transform::Lower], "MiXeD")?, "mixed");
+        assert_eq!(run(&[Transform::Base64], "user:pass")?, "dXNlcjpwYXNz");
+        assert_eq!(
+            run(&[Transform::Base64Decode], "dXNlcjpwYXNz\n")?,
+            "user:pass"
+        );
+        assert_eq!(run(&[Transform::Hex], "hi")?, "6869");
+        assert_eq!(run(&[Transform::HexDecode], "6869")?, "hi");
+        assert_eq!(
+            run(&[Transform::Urlencode], "a b&c=d/é~")?,
+            "a%20b%26c%3Dd%2F%C3%A9~"
+        );
+        assert_eq!(
+            run(&[Transform::JsonEscape], "line\n\"quoted\"\\")?,
+            "line\\n\\\"quoted\\\"\\\\"
+        );
+        assert_eq!(run(&[], "unchanged")?, "unchanged");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashing_transforms() -> Result<()> {
+        assert_eq!(
+            apply_all(&[Transform::Sha256], "password".to_string())?,
+            "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8"
+        );
+
+        let hashed = apply_all(&[Transform::Bcrypt], "password".to_string())?;
+        assert!(bcrypt::verify("password", &hashed)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = apply_all(&[Transform::Base64Decode], "not base64!".to_string()).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Transform base64-decode failed"));
+
+        assert!(apply_all(&[Transform::HexDecode], "zz".to_string()).is_err());
+    }
+}
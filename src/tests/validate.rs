@@ -0,0 +1,163 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{validate, Diagnostic, Severity};
+    use crate::config::load_config;
+    use crate::tests::env_with;
+    use anyhow::Result;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn messages(diagnostics: &[Diagnostic], severity: Severity) -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .map(|d| d.message.replace('\\', "/"))
+            .collect()
+    }
+
+    fn path(dir: &Path, name: &str) -> String {
+        dir.join(name).to_string_lossy().replace('\\', "/")
+    }
+
+    #[tokio::test]
+    async fn test_validate_target_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(
+            dir.join("app.yml"),
+            "database:\n  host: localhost\n  password: x\n",
+        )?;
+        let env = env_with(&[("EA_TEST_036_PASS", "secret"), ("EA_TEST_036_OTHER", "other")]);
+
+        let config_path = dir.join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+[[specific.yaml.locations]]
+file = "{app}"
+node = "database.password"
+variable = "EA_TEST_036_PASS"
+
+[[specific.yaml.locations]]
+file = ["{app}", "{missing}"]
+node = ["database.password", "database.port", " "]
+variable = "EA_TEST_036_OTHER"
+"#,
+                app = path(dir, "app.yml"),
+                missing = path(dir, "missing.yml")
+            ),
+        )?;
+
+        let config = load_config(Some(config_path.clone())).await?;
+        let diagnostics = validate(&config, &env).await;
+        let app = path(dir, "app.yml");
+
+        assert_eq!(
+            messages(&diagnostics, Severity::Error),
+            vec![
+                "Location for `EA_TEST_036_OTHER` has an empty node".to_string(),
+                format!(
+                    "Node `database.password` in {} is written by both `EA_TEST_036_PASS` and `EA_TEST_036_OTHER` (first defined at {}:2:1)",
+                    app,
+                    config_path.display()
+                )
+                .replace('\\', "/"),
+                format!("Node `database.port` not found in {}", app),
+                format!("Target file {} does not exist", path(dir, "missing.yml")),
+            ]
+        );
+        assert!(messages(&diagnostics, Severity::Warning).is_empty());
+
+        let origin = diagnostics[0].origin.as_ref().unwrap();
+        assert_eq!((origin.line, origin.column), (7, 1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_unknown_keys_and_unset_variables() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(dir.join("app.properties"), "db.user=x\n")?;
+
+        fs::write(
+            dir.join("shared.toml"),
+            format!(
+                r#"[environment]
+prefix = "%"
+sufix = "%"
+
+[[specific.properties.locations]]
+file = "{}"
+  nodez = "db.user"
+variable = "EA_TEST_036_UNSET"
+"#,
+                path(dir, "app.properties")
+            ),
+        )?;
+        let config_path = dir.join("config.toml");
+        fs::write(&config_path, "include = \"shared.toml\"\nunknown = true\n")?;
+
+        let config = load_config(Some(config_path)).await?;
+        let diagnostics = validate(&config, &env_with(&[])).await;
+
+        assert_eq!(
+            messages(&diagnostics, Severity::Warning),
+            vec![
+                "Unknown key `sufix`, did you mean `suffix`?",
+                "Unknown key `nodez`, did you mean `node`?",
+                "Unknown key `unknown`",
+                "Variable `EA_TEST_036_UNSET` is not set in any environment source",
+            ]
+        );
+
+        let origins: Vec<String> = diagnostics
+            .iter()
+            .filter_map(|d| d.origin.as_ref())
+            .map(|o| {
+                format!(
+                    "{}:{}:{}",
+                    o.path.file_name().unwrap().to_string_lossy(),
+                    o.line,
+                    o.column
+                )
+            })
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                "shared.toml:3:1",
+                "shared.toml:7:3",
+                "config.toml:2:1",
+                "shared.toml:5:1",
+                "shared.toml:5:1"
+            ]
+        );
+        Ok(())
+    }
+}
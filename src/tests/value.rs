@@ -0,0 +1,143 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::app::parse_variable;
+    use crate::config::ValueType;
+    use anyhow::Result;
+
+    const STRUCTURED: [&str; 4] = ["yaml", "json", "toml", "hocon"];
+
+    #[test]
+    fn test_untyped_keeps_original_text() -> Result<()> {
+        for format in STRUCTURED {
+            assert_eq!(parse_variable(format, "0", None)?, "0");
+            assert_eq!(parse_variable(format, "-0.5", None)?, "-0.5");
+            assert_eq!(parse_variable(format, "1.10", None)?, "1.10");
+            assert_eq!(parse_variable(format, "007", None)?, "\"007\"");
+            assert_eq!(parse_variable(format, "00.5", None)?, "\"00.5\"");
+            assert_eq!(parse_variable(format, "1e400", None)?, "\"1e400\"");
+            assert_eq!(parse_variable(format, "inf", None)?, "\"inf\"");
+            assert_eq!(
+                parse_variable(format, "say \"hi\"", None)?,
+                "\"say \\\"hi\\\"\""
+            );
+        }
+        assert_eq!(
+            parse_variable("json", "12345678901234567890", None)?,
+            "12345678901234567890"
+        );
+        assert_eq!(
+            parse_variable("toml", "12345678901234567890", None)?,
+            "\"12345678901234567890\""
+        );
+        assert_eq!(parse_variable("properties", "007", None)?, "007");
+        assert_eq!(parse_variable("properties", "a \"b\"", None)?, "a \"b\"");
+        assert_eq!(parse_variable("xml", "a & <b>", None)?, "a &amp; &lt;b&gt;");
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_literals() -> Result<()> {
+        for format in STRUCTURED {
+            assert_eq!(
+                parse_variable(format, "true", Some(ValueType::Bool))?,
+                "true"
+            );
+            assert_eq!(
+                parse_variable(format, "OFF", Some(ValueType::Bool))?,
+                "false"
+            );
+            assert_eq!(
+                parse_variable(format, "007", Some(ValueType::Integer))?,
+                "7"
+            );
+            assert_eq!(parse_variable(format, "-0", Some(ValueType::Integer))?, "0");
+            assert_eq!(
+                parse_variable(format, "123", Some(ValueType::String))?,
+                "\"123\""
+            );
+            assert_eq!(parse_variable(format, "a b", Some(ValueType::Raw))?, "a b");
+        }
+
+        assert_eq!(
+            parse_variable("json", "12345678901234567890", Some(ValueType::Integer))?,
+            "12345678901234567890"
+        );
+        assert_eq!(
+            parse_variable("json", "+1.10", Some(ValueType::Float))?,
+            "1.10"
+        );
+        assert_eq!(parse_variable("json", "3", Some(ValueType::Float))?, "3");
+        assert_eq!(parse_variable("toml", "3", Some(ValueType::Float))?, "3.0");
+        assert_eq!(
+            parse_variable("yaml", "1e5", Some(ValueType::Float))?,
+            "1.0e5"
+        );
+        assert_eq!(
+            parse_variable("json", "null", Some(ValueType::Null))?,
+            "null"
+        );
+        assert_eq!(parse_variable("yaml", "", Some(ValueType::Null))?, "null");
+        assert_eq!(
+            parse_variable("properties", "null", Some(ValueType::Null))?,
+            ""
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_datetime_literals() -> Result<()> {
+        let value = "2025-01-02T03:04:05Z";
+        assert_eq!(
+            parse_variable("toml", value, Some(ValueType::Datetime))?,
+            value
+        );
+        assert_eq!(
+            parse_variable("yaml", value, Some(ValueType::Datetime))?,
+            value
+        );
+        assert_eq!(
+            parse_variable("json", value, Some(ValueType::Datetime))?,
+            format!("\"{}\"", value)
+        );
+        assert_eq!(
+            parse_variable("toml", "2025-01-02", Some(ValueType::Datetime))?,
+            "2025-01-02"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_typed_values() {
+        assert!(parse_variable("json", "12a", Some(ValueType::Integer)).is_err());
+        assert!(parse_variable("toml", "12345678901234567890", Some(ValueType::Integer)).is_err());
+        assert!(parse_variable("json", "NaN", Some(ValueType::Float)).is_err());
+        assert!(parse_variable("json", "maybe", Some(ValueType::Bool)).is_err());
+        assert!(parse_variable("json", "value", Some(ValueType::Null)).is_err());
+        assert!(parse_variable("toml", "null", Some(ValueType::Null)).is_err());
+        assert!(parse_variable("toml", "2025-13-01", Some(ValueType::Datetime)).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 darksaid98
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::config::VaultSource;
+    use crate::env::vault::{parse_reference, VaultError};
+    use crate::env::{load, Options};
+    use anyhow::Result;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const APP_SECRET: &str =
+        r#"{"data": {"data": {"EA_TEST_048_API_KEY": "from-vault", "EA_TEST_048_PORT": 8080}}}"#;
+    const DB_SECRET: &str = r#"{"data": {"data": {"password": "hunter2"}}}"#;
+
+    /// Serves canned `(request line prefix, status, body)` responses on a local port and
+    /// records every request received.
+    fn serve(routes: Vec<(&'static str, u16, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+
+                let (status, body) = routes
+                    .iter()
+                    .find(|(prefix, _, _)| request.starts_with(prefix))
+                    .map_or((404, r#"{"errors": []}"#), |(_, status, body)| {
+                        (*status, *body)
+                    });
+                recorded.lock().unwrap().push(request);
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Vault\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        (address, requests)
+    }
+
+    fn source(address: &str) -> VaultSource {
+        VaultSource {
+            address: Some(address.to_string()),
+            mount: "secret".into(),
+            path: Some("app".into()),
+            timeout: 5,
+        }
+    }
+
+    #[test]
+    fn test_vault_token() -> Result<()> {
+        let (address, requests) = serve(vec![
+            ("GET /v1/secret/data/app ", 200, APP_SECRET),
+            ("GET /v1/secret/data/db/creds ", 200, DB_SECRET),
+        ]);
+        let temp_dir = TempDir::new()?;
+        let env_file = temp_dir.path().join("app.env");
+        fs::write(
+            &env_file,
+            "VAULT_TOKEN=s.token\nEA_TEST_048_API_KEY=from-file\nEA_TEST_048_OTHER=from-file\n",
+        )?;
+
+        let env = load(&Options {
+            no_dotenv: true,
+            env_files: vec![env_file],
+            vault: Some(source(&address)),
+            vault_references: vec!["vault:db/creds#password".into()],
+            ..Default::default()
+        })?;
+
+        assert_eq!(
+            env.get("EA_TEST_048_API_KEY").as_deref(),
+            Some("from-vault")
+        );
+        assert_eq!(env.get("EA_TEST_048_PORT").as_deref(), Some("8080"));
+        assert_eq!(env.get("EA_TEST_048_OTHER").as_deref(), Some("from-file"));
+        assert_eq!(
+            env.get("vault:db/creds#password").as_deref(),
+            Some("hunter2")
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|r| r.to_lowercase().contains("x-vault-token: s.token")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_is_read_on_demand() -> Result<()> {
+        let (address, requests) = serve(vec![
+            ("GET /v1/secret/data/app ", 200, APP_SECRET),
+            ("GET /v1/secret/data/db ", 200, DB_SECRET),
+        ]);
+
+        let env = load(&Options {
+            no_dotenv: true,
+            overrides: vec![("VAULT_TOKEN".into(), "s.token".into())],
+            vault: Some(VaultSource {
+                path: None,
+                ..source(&address)
+            }),
+            vault_references: vec![
+                "vault:app#EA_TEST_048_PORT".into(),
+                "vault:db#password".into(),
+            ],
+            ..Default::default()
+        })?;
+        assert!(requests.lock().unwrap().is_empty());
+
+        assert_eq!(env.get("vault:db#password").as_deref(), Some("hunter2"));
+        assert_eq!(env.get("vault:db#password").as_deref(), Some("hunter2"));
+        assert_eq!(env.get("EA_TEST_048_UNSET"), None);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /v1/secret/data/db "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_approle() -> Result<()> {
+        let (address, requests) = serve(vec![
+            (
+                "POST /v1/auth/approle/login ",
+                200,
+                r#"{"auth": {"client_token": "s.approle"}}"#,
+            ),
+            ("GET /v1/secret/data/app ", 200, APP_SECRET),
+        ]);
+
+        let env = load(&Options {
+            no_dotenv: true,
+            overrides: vec![
+                ("VAULT_ROLE_ID".into(), "role".into()),
+                ("VAULT_SECRET_ID".into(), "secret".into()),
+            ],
+            vault: Some(source(&address)),
+            ..Default::default()
+        })?;
+
+        assert_eq!(
+            env.get("EA_TEST_048_API_KEY").as_deref(),
+            Some("from-vault")
+        );
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains(r#""role_id":"role""#));
+        assert!(requests[0].contains(r#""secret_id":"secret""#));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("x-vault-token: s.approle"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_errors() -> Result<()> {
+        let (address, _) = serve(vec![
+            (
+                "GET /v1/secret/data/app ",
+                403,
+                r#"{"errors": ["permission denied"]}"#,
+            ),
+            ("GET /v1/secret/data/db ", 200, DB_SECRET),
+        ]);
+        let options = |token: bool| Options {
+            no_dotenv: true,
+            overrides: if token {
+                vec![("VAULT_TOKEN".into(), "s.token".into())]
+            } else {
+                Vec::new()
+            },
+            vault: Some(source(&address)),
+            ..Default::default()
+        };
+
+        let env = load(&options(true))?;
+        assert_eq!(env.get("EA_TEST_048_API_KEY"), None);
+        let err = env.source_error("EA_TEST_048_API_KEY").unwrap();
+        assert!(err.contains("Vault returned 403"), "{}", err);
+        assert!(err.contains("permission denied"), "{}", err);
+
+        assert_eq!(env.get("vault:db#user"), None);
+        assert_eq!(
+            env.source_error("vault:db#user").as_deref(),
+            Some("Vault secret `db` has no key `user`")
+        );
+        assert_eq!(env.source_error("vault:db#password"), None);
+        assert_eq!(
+            env.source_error("vault:db").as_deref(),
+            Some("Invalid Vault reference `vault:db`, expected `vault:<path>#<key>`")
+        );
+
+        if std::env::var("VAULT_TOKEN").is_err() && std::env::var("VAULT_ROLE_ID").is_err() {
+            let env = load(&options(false))?;
+            assert_eq!(
+                env.source_error("vault:db#password"),
+                Some(VaultError::MissingCredentials.to_string())
+            );
+        }
+
+        let err = load(&Options {
+            no_dotenv: true,
+            vault_references: vec!["vault:db#password".into()],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`vault:db#password` is used but [sources.vault] is not configured"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_timeout() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        // Accepts the connection but never answers
+        thread::spawn(move || {
+            let _stream = listener.accept();
+            thread::sleep(Duration::from_secs(5));
+        });
+
+        let env = load(&Options {
+            no_dotenv: true,
+            overrides: vec![("VAULT_TOKEN".into(), "s.token".into())],
+            vault: Some(VaultSource {
+                timeout: 1,
+                ..source(&address)
+            }),
+            ..Default::default()
+        })?;
+        let err = env.source_error("EA_TEST_048_API_KEY").unwrap();
+        assert!(
+            err.starts_with(&format!("Request to {}/v1/secret/data/app failed", address)),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_vault_reference() {
+        assert_eq!(
+            parse_reference("vault:db/creds#password").unwrap(),
+            ("db/creds", "password")
+        );
+        for reference in [
+            "vault:db/creds",
+            "vault:#password",
+            "vault:db#",
+            "db#password",
+        ] {
+            assert!(parse_reference(reference).is_err(), "{}", reference);
+        }
+    }
+}
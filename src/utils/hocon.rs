@@ -27,7 +27,6 @@ use regex::Regex;
 use std::path::Path;
 use tokio::fs;
 
-#[allow(unused_assignments)]
 pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &str) -> Result<()> {
     let hocon_content = fs::read_to_string(file_path).await?;
 
@@ -50,9 +49,70 @@ pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &st
     let mut lines: Vec<String> = main_content.lines().map(|d| d.to_string()).collect();
     let path_parts = parse_hocon_path(node_path);
 
+    if let Some((target_index, current_depth)) = locate_node(&lines, &path_parts) {
+        let line = &lines[target_index];
+
+        // Preserve comments at the end of the line
+        let comment_patterns = [
+            r"(\s+#.*)$",     // # comments require whitespace
+            r"(\s+//.*)$",    // // comments require whitespace
+        ];
+
+        let mut comment = String::new();
+        for pattern in &comment_patterns {
+            let comment_re = Regex::new(pattern).unwrap();
+            if let Some(caps) = comment_re.captures(line) {
+                comment = caps[1].to_string();
+                break;
+            }
+        }
+
+        let indent = " ".repeat(current_depth);
+        let key = &path_parts[path_parts.len() - 1];
+
+        // Format the new value based on its type
+        let formatted_value = format_hocon_value(new_value);
+
+        // Determine the assignment operator to use based on the original line
+        let assignment_op = if line.contains(" = ") || line.contains("=") {
+            " = "
+        } else {
+            ": "
+        };
+
+        // Handle quoted keys if the original was quoted
+        let formatted_key = if line.trim_start().starts_with('"') {
+            format!("\"{}\"", key)
+        } else {
+            key.to_string()
+        };
+
+        lines[target_index] = format!(
+            "{}{}{}{}{}",
+            indent, formatted_key, assignment_op, formatted_value, comment
+        );
+    }
+
+    let new_main_content = lines.join("\n");
+    let new_content = format!("{}{}", new_main_content, trailing_newlines);
+
+    fs::write(file_path, new_content).await?;
+    Ok(())
+}
+
+/// Returns the line of a node in HOCON content, or `None` if the node doesn't exist.
+pub fn find_hocon_node(content: &str, node_path: &str) -> Option<usize> {
+    let lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
+
+    locate_node(&lines, &parse_hocon_path(node_path)).map(|(line, _)| line)
+}
+
+/// Finds the line holding the node and its indentation.
+#[allow(unused_assignments)]
+fn locate_node(lines: &[String], path_parts: &[String]) -> Option<(usize, usize)> {
     // Detect indentation from first non-empty line
     let mut indent_size = 2;
-    for line in &lines {
+    for line in lines {
         let trimmed = line.trim();
         if trimmed.starts_with('#') || trimmed.starts_with("//") || trimmed.is_empty() {
             continue;
@@ -71,8 +131,7 @@ pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &st
     }
 
     let mut current_depth = 0;
-    let mut path_index = 0;
-    let mut target_line = None;
+    let mut path_index: usize = 0;
     let mut depth_stack = Vec::new(); // Track nesting depth
 
     for (i, line) in lines.iter().enumerate() {
@@ -89,9 +148,7 @@ pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &st
         // Adjust current depth based on indentation changes
         while !depth_stack.is_empty() && line_indent <= *depth_stack.last().unwrap() {
             depth_stack.pop();
-            if path_index > 0 {
-                path_index -= 1;
-            }
+            path_index = path_index.saturating_sub(1);
         }
         current_depth = line_indent;
 
@@ -136,8 +193,7 @@ pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &st
                 key == path_parts[path_index] {
                 if path_index == path_parts.len() - 1 {
                     // Found our target key-value pair
-                    target_line = Some(i);
-                    break;
+                    return Some((i, current_depth));
                 } else {
                     // This is part of our path, continue deeper
                     path_index += 1;
@@ -152,55 +208,7 @@ pub async fn update_hocon_node(file_path: &Path, node_path: &str, new_value: &st
         }
     }
 
-    if let Some(target_index) = target_line {
-        let line = &lines[target_index];
-
-        // Preserve comments at the end of the line
-        let comment_patterns = [
-            r"(\s+#.*)$",     // # comments require whitespace
-            r"(\s+//.*)$",    // // comments require whitespace
-        ];
-
-        let mut comment = String::new();
-        for pattern in &comment_patterns {
-            let comment_re = Regex::new(pattern).unwrap();
-            if let Some(caps) = comment_re.captures(line) {
-                comment = caps[1].to_string();
-                break;
-            }
-        }
-
-        let indent = " ".repeat(current_depth);
-        let key = &path_parts[path_index];
-
-        // Format the new value based on its type
-        let formatted_value = format_hocon_value(new_value);
-
-        // Determine the assignment operator to use based on the original line
-        let assignment_op = if line.contains(" = ") || line.contains("=") {
-            " = "
-        } else {
-            ": "
-        };
-
-        // Handle quoted keys if the original was quoted
-        let formatted_key = if line.trim_start().starts_with('"') {
-            format!("\"{}\"", key)
-        } else {
-            key.to_string()
-        };
-
-        lines[target_index] = format!(
-            "{}{}{}{}{}",
-            indent, formatted_key, assignment_op, formatted_value, comment
-        );
-    }
-
-    let new_main_content = lines.join("\n");
-    let new_content = format!("{}{}", new_main_content, trailing_newlines);
-
-    fs::write(file_path, new_content).await?;
-    Ok(())
+    None
 }
 
 fn parse_hocon_path(path: &str) -> Vec<String> {
@@ -45,9 +45,49 @@ pub async fn update_json_node(file_path: &Path, node_path: &str, new_value: &str
     let mut lines: Vec<String> = json_content.lines().map(|d| d.to_string()).collect();
     let path_parts: Vec<&str> = node_path.split('.').collect();
 
+    // Update the target line if found
+    if let Some((target_index, current_depth)) = locate_node(&lines, &path_parts)? {
+        let line = &lines[target_index];
+
+        // Preserve any trailing comment
+        let comment_re = Regex::new(r"(//.*)$")?;
+        let comment = comment_re
+            .captures(line)
+            .map_or("".to_string(), |caps| caps[1].to_string());
+
+        // Preserve trailing comma if present
+        let comma_re = Regex::new(r",\s*(//.*)?")?;
+        let has_comma = comma_re.is_match(line);
+
+        let indent = " ".repeat(current_depth);
+        lines[target_index] = format!(
+            "{}\"{}\": {}{}{}",
+            indent,
+            path_parts[path_parts.len() - 1],
+            new_value,
+            if has_comma { "," } else { "" },
+            comment
+        );
+    }
+
+    // Write the modified content back to the file
+    fs::write(file_path, lines.join("\n")).await?;
+    Ok(())
+}
+
+/// Returns the line of a node in JSON content, or `None` if the node doesn't exist.
+pub fn find_json_node(content: &str, node_path: &str) -> Result<Option<usize>> {
+    let lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
+    let path_parts: Vec<&str> = node_path.split('.').collect();
+
+    Ok(locate_node(&lines, &path_parts)?.map(|(line, _)| line))
+}
+
+/// Finds the line holding the node and its indentation.
+fn locate_node(lines: &[String], path_parts: &[&str]) -> Result<Option<(usize, usize)>> {
     // Detect the file's indentation style from the first indented line
     let mut indent_size = 2;
-    for line in &lines {
+    for line in lines {
         let trimmed = line.trim();
         if trimmed.starts_with("//") || trimmed.is_empty() {
             continue;
@@ -67,7 +107,6 @@ pub async fn update_json_node(file_path: &Path, node_path: &str, new_value: &str
 
     let mut current_depth = 0;
     let mut path_index = 0;
-    let mut target_line = None;
 
     // Find the target line by traversing the path
     for (i, line) in lines.iter().enumerate() {
@@ -86,40 +125,12 @@ pub async fn update_json_node(file_path: &Path, node_path: &str, new_value: &str
 
         if let Some(_captures) = re.captures(line) {
             if path_index == path_parts.len() - 1 {
-                target_line = Some(i);
-                break;
+                return Ok(Some((i, current_depth)));
             }
             path_index += 1;
             current_depth += indent_size;
         }
     }
 
-    // Update the target line if found
-    if let Some(target_index) = target_line {
-        let line = &lines[target_index];
-
-        // Preserve any trailing comment
-        let comment_re = Regex::new(r"(//.*)$")?;
-        let comment = comment_re
-            .captures(line)
-            .map_or("".to_string(), |caps| caps[1].to_string());
-
-        // Preserve trailing comma if present
-        let comma_re = Regex::new(r",\s*(//.*)?")?;
-        let has_comma = comma_re.is_match(line);
-
-        let indent = " ".repeat(current_depth);
-        lines[target_index] = format!(
-            "{}\"{}\": {}{}{}",
-            indent,
-            path_parts[path_index],
-            new_value,
-            if has_comma { "," } else { "" },
-            comment
-        );
-    }
-
-    // Write the modified content back to the file
-    fs::write(file_path, lines.join("\n")).await?;
-    Ok(())
+    Ok(None)
 }
@@ -41,27 +41,9 @@ use tokio::fs;
 pub async fn update_properties_node(file_path: &Path, key: &str, new_value: &str) -> Result<()> {
     let content = fs::read_to_string(file_path).await?;
     let mut lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
-    let mut target_line = None;
-
-    // Find the line containing the target key
-    for (i, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('#') || trimmed.is_empty() {
-            continue;
-        }
-
-        // Simple approach: find key at start, then find = and capture parts
-        if let Some(eq_pos) = line.find('=') {
-            let key_part = line[..eq_pos].trim();
-            if key_part == key {
-                target_line = Some(i);
-                break;
-            }
-        }
-    }
 
     // Update the target line if found
-    if let Some(target_index) = target_line {
+    if let Some(target_index) = find_key(&lines, key) {
         let line = &lines[target_index];
 
         // Find the = position
@@ -128,3 +110,30 @@ pub async fn update_properties_node(file_path: &Path, key: &str, new_value: &str
     fs::write(file_path, lines.join("\n")).await?;
     Ok(())
 }
+
+/// Returns the line of a key in properties content, or `None` if the key doesn't exist.
+pub fn find_properties_node(content: &str, key: &str) -> Option<usize> {
+    let lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
+
+    find_key(&lines, key)
+}
+
+fn find_key(lines: &[String], key: &str) -> Option<usize> {
+    // Find the line containing the target key
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.is_empty() {
+            continue;
+        }
+
+        // Simple approach: find key at start, then find = and capture parts
+        if let Some(eq_pos) = line.find('=') {
+            let key_part = line[..eq_pos].trim();
+            if key_part == key {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
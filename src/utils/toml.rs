@@ -42,11 +42,37 @@ use tokio::fs;
 pub async fn update_toml_node(file_path: &Path, node_path: &str, new_value: &str) -> Result<()> {
     let toml_content = fs::read_to_string(file_path).await?;
     let mut lines: Vec<String> = toml_content.lines().map(|s| s.to_string()).collect();
+
+    // Update the target line if found, preserving original formatting
+    if let Some((target_index, prefix, comment)) = locate_node(&lines, node_path)? {
+        lines[target_index] = format!(
+            "{}{}{}",
+            prefix,
+            new_value,
+            comment.unwrap_or_default()
+        );
+    }
+
+    fs::write(file_path, lines.join("\n")).await?;
+    Ok(())
+}
+
+/// Returns the line of a node in TOML content, or `None` if the node doesn't exist.
+pub fn find_toml_node(content: &str, node_path: &str) -> Result<Option<usize>> {
+    let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+
+    Ok(locate_node(&lines, node_path)?.map(|(line, _, _)| line))
+}
+
+/// Finds the line holding the node, along with the text before its value and any trailing comment.
+fn locate_node(lines: &[String], node_path: &str) -> Result<Option<(usize, String, Option<String>)>> {
     let path_parts: Vec<&str> = node_path.split('.').collect();
 
     let mut current_array = Vec::new();
     let mut current_table = Vec::new();
-    let mut target_line = None;
+
+    let array_re = Regex::new(r"^\[\[([^\]]+)\]\]$")?;
+    let table_re = Regex::new(r"^\[([^\]]+)\]$")?;
 
     // Traverse the file looking for the target node
     for (i, line) in lines.iter().enumerate() {
@@ -59,13 +85,11 @@ pub async fn update_toml_node(file_path: &Path, node_path: &str, new_value: &str
         if trimmed.starts_with('[') {
             if trimmed.starts_with("[[") && trimmed.ends_with("]]") {
                 // Array of tables (e.g., `[[items]]`)
-                let array_re = Regex::new(r"^\[\[([^\]]+)\]\]$")?;
                 if let Some(captures) = array_re.captures(trimmed) {
                     current_array = captures[1].split('.').map(|s| s.to_string()).collect();
                 }
             } else {
                 // Regular table (e.g., `[table]`)
-                let table_re = Regex::new(r"^\[([^\]]+)\]$")?;
                 if let Some(captures) = table_re.captures(trimmed) {
                     current_table = captures[1].split('.').map(|s| s.to_string()).collect();
                     current_array.clear(); // Reset array context when entering a new table
@@ -99,26 +123,14 @@ pub async fn update_toml_node(file_path: &Path, node_path: &str, new_value: &str
             };
 
             if full_path == node_path {
-                target_line = Some((
+                return Ok(Some((
                     i,
                     captures[1].to_string(),
                     captures.get(3).map(|m| m.as_str().to_string()),
-                ));
-                break;
+                )));
             }
         }
     }
 
-    // Update the target line if found, preserving original formatting
-    if let Some((target_index, prefix, comment)) = target_line {
-        lines[target_index] = format!(
-            "{}{}{}",
-            prefix,
-            new_value,
-            comment.map(|c| format!("{}", c)).unwrap_or_default()
-        );
-    }
-
-    fs::write(file_path, lines.join("\n")).await?;
-    Ok(())
+    Ok(None)
 }
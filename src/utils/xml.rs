@@ -33,13 +33,41 @@ use tokio::fs;
 pub async fn update_xml_node(file_path: &Path, node_path: &str, new_value: &str) -> Result<()> {
     let xml_content = fs::read_to_string(file_path).await?;
     let mut lines: Vec<String> = xml_content.lines().map(|d| d.to_string()).collect();
+
+    // Update the target line while preserving formatting
+    if let Some(target_index) = locate_node(&lines, node_path)? {
+        let line = &lines[target_index];
+        let opening_tag_re = Regex::new(r"^(\s*<[^>]+>)([^<]*)(</[^>]+>)(.*)$").unwrap();
+
+        if let Some(captures) = opening_tag_re.captures(line) {
+            let prefix = captures.get(1).unwrap().as_str();
+            let suffix = captures.get(3).unwrap().as_str();
+            let trailing = captures.get(4).map_or("", |m| m.as_str());
+
+            lines[target_index] = format!("{}{}{}{}", prefix, new_value, suffix, trailing);
+        }
+    }
+
+    fs::write(file_path, lines.join("\n")).await?;
+    Ok(())
+}
+
+/// Returns the line of a node in XML content, or `None` if the node doesn't exist.
+///
+/// Fails if the node is a self-closing tag, which cannot hold a value.
+pub fn find_xml_node(content: &str, node_path: &str) -> Result<Option<usize>> {
+    let lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
+
+    locate_node(&lines, node_path)
+}
+
+fn locate_node(lines: &[String], node_path: &str) -> Result<Option<usize>> {
     let path_parts: Vec<&str> = node_path.split('.').collect();
 
     // Track current state
     let mut _current_depth = 0;
     let mut path_index = 0;
     let mut in_target_path = false;
-    let mut target_line = None;
     let mut found_nodes = Vec::new();
 
     // Regex patterns
@@ -50,7 +78,7 @@ pub async fn update_xml_node(file_path: &Path, node_path: &str, new_value: &str)
 
     // First pass: detect indentation
     let mut indent_size = 2;
-    for line in &lines {
+    for line in lines {
         let trimmed = line.trim();
         if trimmed.starts_with("<!--") || trimmed.is_empty() {
             continue;
@@ -85,11 +113,12 @@ pub async fn update_xml_node(file_path: &Path, node_path: &str, new_value: &str)
         // Handle self-closing tags
         if let Some(captures) = self_closing_re.captures(line) {
             let tag_name = captures.get(2).unwrap().as_str();
-            if in_target_path && tag_name == path_parts[path_index] {
-                if path_index == path_parts.len() - 1 {
-                    // Can't update self-closing tags
-                    return Err(anyhow!("Cannot update self-closing tags"));
-                }
+            if in_target_path
+                && tag_name == path_parts[path_index]
+                && path_index == path_parts.len() - 1
+            {
+                // Can't update self-closing tags
+                return Err(anyhow!("Cannot update self-closing tags"));
             }
             continue;
         }
@@ -120,8 +149,7 @@ pub async fn update_xml_node(file_path: &Path, node_path: &str, new_value: &str)
                 _current_depth += indent_size;
 
                 if path_index == path_parts.len() {
-                    target_line = Some(i);
-                    break;
+                    return Ok(Some(i));
                 }
             } else if in_target_path && tag_name == path_parts[path_index] {
                 found_nodes.push(tag_name);
@@ -129,29 +157,13 @@ pub async fn update_xml_node(file_path: &Path, node_path: &str, new_value: &str)
                 _current_depth += indent_size;
 
                 if path_index == path_parts.len() {
-                    target_line = Some(i);
-                    break;
+                    return Ok(Some(i));
                 }
             }
         }
     }
 
-    // Update the target line while preserving formatting
-    if let Some(target_index) = target_line {
-        let line = &lines[target_index];
-        let opening_tag_re = Regex::new(r"^(\s*<[^>]+>)([^<]*)(</[^>]+>)(.*)$").unwrap();
-
-        if let Some(captures) = opening_tag_re.captures(line) {
-            let prefix = captures.get(1).unwrap().as_str();
-            let suffix = captures.get(3).unwrap().as_str();
-            let trailing = captures.get(4).map_or("", |m| m.as_str());
-
-            lines[target_index] = format!("{}{}{}{}", prefix, new_value, suffix, trailing);
-        }
-    }
-
-    fs::write(file_path, lines.join("\n")).await?;
-    Ok(())
+    Ok(None)
 }
 
 pub fn _parse_variable(env: &str) -> String {
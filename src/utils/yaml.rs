@@ -49,9 +49,40 @@ pub async fn update_yaml_node(file_path: &Path, node_path: &str, new_value: &str
     let mut lines: Vec<String> = main_content.lines().map(|d| d.to_string()).collect();
     let path_parts: Vec<&str> = node_path.split('.').collect();
 
+    if let Some((target_index, current_depth)) = locate_node(&lines, &path_parts) {
+        let line = &lines[target_index];
+        let comment_re = regex::Regex::new(r"(\s*#.*)$").unwrap();
+        let comment = comment_re
+            .captures(line)
+            .map_or("".to_string(), |caps| caps[1].to_string());
+        let indent = " ".repeat(current_depth);
+
+        lines[target_index] = format!(
+            "{}{}: {}{}",
+            indent, path_parts[path_parts.len() - 1], new_value, comment
+        );
+    }
+
+    let new_main_content = lines.join("\n");
+    let new_content = format!("{}{}", new_main_content, trailing_newlines);
+
+    fs::write(file_path, new_content).await?;
+    Ok(())
+}
+
+/// Returns the line of a node in YAML content, or `None` if the node doesn't exist.
+pub fn find_yaml_node(content: &str, node_path: &str) -> Option<usize> {
+    let lines: Vec<String> = content.lines().map(|d| d.to_string()).collect();
+    let path_parts: Vec<&str> = node_path.split('.').collect();
+
+    locate_node(&lines, &path_parts).map(|(line, _)| line)
+}
+
+/// Finds the line holding the node and its indentation.
+fn locate_node(lines: &[String], path_parts: &[&str]) -> Option<(usize, usize)> {
     // Detect indentation from first non-empty line
     let mut indent_size = 2;
-    for line in &lines {
+    for line in lines {
         let trimmed = line.trim();
         if trimmed.starts_with('#') || trimmed.is_empty() {
             continue;
@@ -71,7 +102,6 @@ pub async fn update_yaml_node(file_path: &Path, node_path: &str, new_value: &str
 
     let mut current_depth = 0;
     let mut path_index = 0;
-    let mut target_line = None;
 
     for (i, line) in lines.iter().enumerate() {
         let trimmed = line.trim();
@@ -89,8 +119,7 @@ pub async fn update_yaml_node(file_path: &Path, node_path: &str, new_value: &str
         if let Some(captures) = re.captures(line) {
             if captures.get(1).map_or("", |m| m.as_str()) == path_parts[path_index] {
                 if path_index == path_parts.len() - 1 {
-                    target_line = Some(i);
-                    break;
+                    return Some((i, current_depth));
                 }
                 path_index += 1;
                 current_depth += indent_size;
@@ -98,24 +127,5 @@ pub async fn update_yaml_node(file_path: &Path, node_path: &str, new_value: &str
         }
     }
 
-    if let Some(target_index) = target_line {
-        let line = &lines[target_index];
-        let comment_re = regex::Regex::new(r"(\s*#.*)$").unwrap();
-        let comment = comment_re
-            .captures(line)
-            .map_or("".to_string(), |caps| caps[1].to_string());
-        let indent = " ".repeat(current_depth);
-
-        lines[target_index] = format!(
-            "{}{}: {}{}",
-            indent, path_parts[path_index], new_value, comment
-        );
-    }
-
-    let new_main_content = lines.join("\n");
-    let new_content = format!("{}{}", new_main_content, trailing_newlines);
-
-    fs::write(file_path, new_content).await?;
-    Ok(())
+    None
 }
-